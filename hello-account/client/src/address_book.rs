@@ -0,0 +1,211 @@
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{write_keypair_file, Keypair, Signer},
+};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::config::config_dir;
+
+const ADDRESS_BOOK_FILE: &str = "address_book.json";
+const KEYS_DIR: &str = "keys";
+
+/// A named greeting account, optionally with the keypair it was created with.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Entry {
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keypair: Option<PathBuf>,
+}
+
+/// Aliases for greeting accounts, stored as JSON in the config directory.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AddressBook {
+    accounts: BTreeMap<String, Entry>,
+}
+
+impl AddressBook {
+    fn path() -> PathBuf {
+        config_dir().join(ADDRESS_BOOK_FILE)
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(config_dir())?;
+        self.save_to(&Self::path())
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    fn save_to(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &Entry)> {
+        self.accounts.iter()
+    }
+
+    /// Accepts either a base58 address or an alias from the book.
+    pub fn resolve(&self, account: &str) -> Result<Pubkey> {
+        if let Ok(pubkey) = Pubkey::from_str(account) {
+            return Ok(pubkey);
+        }
+
+        let entry = self
+            .accounts
+            .get(account)
+            .ok_or_else(|| anyhow!("`{}` is neither an address nor a known alias (see `accounts list`)", account))?;
+        Ok(Pubkey::from_str(&entry.address)?)
+    }
+
+    /// Fails if `alias` is malformed or already taken, before any transaction
+    /// is sent on its behalf.
+    pub fn check_available(&self, alias: &str) -> Result<()> {
+        validate_alias(alias)?;
+        if self.accounts.contains_key(alias) {
+            bail!("Alias `{}` already exists; `accounts rename` or `accounts forget` it first", alias);
+        }
+        Ok(())
+    }
+
+    pub fn add(&mut self, alias: &str, address: Pubkey) -> Result<()> {
+        self.check_available(alias)?;
+        self.accounts.insert(
+            alias.to_string(),
+            Entry {
+                address: address.to_string(),
+                keypair: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Adds `alias` and writes the greeting keypair next to the book.
+    pub fn add_with_keypair(&mut self, alias: &str, keypair: &Keypair) -> Result<PathBuf> {
+        self.check_available(alias)?;
+
+        let keys_dir = config_dir().join(KEYS_DIR);
+        fs::create_dir_all(&keys_dir)?;
+        let keypair_path = keys_dir.join(format!("{}.json", alias));
+        write_keypair_file(keypair, &keypair_path)
+            .map_err(|err| anyhow!("Failed to write {}: {}", keypair_path.display(), err))?;
+
+        self.accounts.insert(
+            alias.to_string(),
+            Entry {
+                address: keypair.pubkey().to_string(),
+                keypair: Some(keypair_path.clone()),
+            },
+        );
+        Ok(keypair_path)
+    }
+
+    pub fn rename(&mut self, old: &str, new: &str) -> Result<()> {
+        self.check_available(new)?;
+        let entry = self
+            .accounts
+            .remove(old)
+            .ok_or_else(|| anyhow!("Unknown alias `{}`", old))?;
+        self.accounts.insert(new.to_string(), entry);
+        Ok(())
+    }
+
+    /// Removes `alias`; the saved keypair file is kept unless `delete_keypair`.
+    pub fn forget(&mut self, alias: &str, delete_keypair: bool) -> Result<Entry> {
+        let entry = self
+            .accounts
+            .remove(alias)
+            .ok_or_else(|| anyhow!("Unknown alias `{}`", alias))?;
+
+        if let (true, Some(path)) = (delete_keypair, &entry.keypair) {
+            fs::remove_file(path).with_context(|| format!("Failed to delete {}", path.display()))?;
+        }
+        Ok(entry)
+    }
+}
+
+fn validate_alias(alias: &str) -> Result<()> {
+    if alias.is_empty() || !alias.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)) {
+        bail!("Alias `{}` may only contain letters, digits, `-`, `_` and `.`", alias);
+    }
+    // An alias that parses as an address would never be looked up
+    if Pubkey::from_str(alias).is_ok() {
+        bail!("Alias `{}` looks like an address", alias);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliases_resolve_until_forgotten() {
+        let mut book = AddressBook::default();
+        let address = Pubkey::new_unique();
+        book.add("alice", address).unwrap();
+
+        assert_eq!(book.resolve("alice").unwrap(), address);
+        // Addresses resolve to themselves without an entry
+        let other = Pubkey::new_unique();
+        assert_eq!(book.resolve(&other.to_string()).unwrap(), other);
+
+        let entry = book.forget("alice", false).unwrap();
+        assert_eq!(entry.address, address.to_string());
+        assert!(book.resolve("alice").is_err());
+        assert!(book.forget("alice", false).is_err());
+    }
+
+    #[test]
+    fn aliases_are_unique_and_well_formed() {
+        let mut book = AddressBook::default();
+        book.add("alice", Pubkey::new_unique()).unwrap();
+
+        let err = book.add("alice", Pubkey::new_unique()).unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{}", err);
+        assert!(book.rename("alice", "alice").is_err());
+        book.add("bob", Pubkey::new_unique()).unwrap();
+        assert!(book.rename("bob", "alice").is_err());
+
+        for alias in ["", "with space", "a/b", "émoji", &Pubkey::new_unique().to_string()] {
+            assert!(book.add(alias, Pubkey::new_unique()).is_err(), "{}", alias);
+        }
+        assert_eq!(book.entries().count(), 2);
+    }
+
+    #[test]
+    fn books_survive_a_save_and_load() {
+        let path = std::env::temp_dir().join(format!("hello-account-address-book-{}.json", std::process::id()));
+        let mut book = AddressBook::default();
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        book.add("alice", alice).unwrap();
+        book.add("bob", bob).unwrap();
+        book.rename("bob", "robert").unwrap();
+
+        book.save_to(&path).unwrap();
+        let loaded = AddressBook::load_from(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.resolve("alice").unwrap(), alice);
+        assert_eq!(loaded.resolve("robert").unwrap(), bob);
+        assert!(loaded.resolve("bob").is_err());
+        assert!(AddressBook::load_from(&path).unwrap().entries().next().is_none(), "a missing book loads empty");
+    }
+}
@@ -0,0 +1,562 @@
+use anyhow::{bail, Context, Result};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    address_lookup_table::{state::LOOKUP_TABLE_MAX_ADDRESSES, AddressLookupTableAccount},
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::Instruction,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+    str::FromStr,
+};
+
+use hello_account::state::MAX_MESSAGE_LEN;
+
+use hello_account_client::{
+    instructions,
+    transaction::{compile_message, SendConfig},
+    GreetingClient,
+};
+
+use crate::address_book::AddressBook;
+
+// Below this many existing accounts a lookup table saves less than it costs
+const AUTO_LOOKUP_TABLE_MIN_ACCOUNTS: usize = 16;
+
+// Accounts a transaction may lock, whether listed or loaded from a table
+const MAX_ACCOUNTS_PER_TRANSACTION: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Op {
+    Init,
+    Update,
+    Increment,
+    Close,
+}
+
+/// One manifest entry. `account` is required for everything but `init`,
+/// `owner` only applies to `init` and `message`, required, only to `update`.
+#[derive(Debug, Deserialize)]
+struct ManifestRow {
+    op: Op,
+    account: Option<String>,
+    owner: Option<String>,
+    message: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Ok,
+    Failed,
+}
+
+/// Outcome of one manifest row, as written to the report file.
+#[derive(Debug, Deserialize, Serialize)]
+struct ReportRow {
+    row: usize,
+    op: Op,
+    account: Option<String>,
+    status: Status,
+    signature: Option<String>,
+    error: Option<String>,
+}
+
+/// A manifest row turned into an instruction, plus the fresh greeting
+/// keypair that has to co-sign it when the row is an `init`.
+struct Operation {
+    row: usize,
+    op: Op,
+    greeting: Pubkey,
+    instruction: Instruction,
+    greeting_keypair: Option<Keypair>,
+}
+
+/// Operations packed into a single transaction.
+struct Batch {
+    operations: Vec<Operation>,
+}
+
+impl Batch {
+    fn instructions(&self) -> Vec<Instruction> {
+        self.operations.iter().map(|op| op.instruction.clone()).collect()
+    }
+
+    fn accounts(&self) -> HashSet<Pubkey> {
+        self.operations.iter().map(|op| op.greeting).collect()
+    }
+
+    /// The payer, the owner when an instruction needs its signature (`init`
+    /// rows do not) and the keypairs of new greetings.
+    fn signers<'a>(&'a self, payer: &'a Keypair, owner: &'a Keypair) -> Vec<&'a dyn Signer> {
+        let mut signers: Vec<&dyn Signer> = vec![payer];
+        let owner_signs = self
+            .operations
+            .iter()
+            .flat_map(|op| &op.instruction.accounts)
+            .any(|meta| meta.is_signer && meta.pubkey == owner.pubkey());
+        if owner_signs {
+            signers.push(owner);
+        }
+        signers.extend(
+            self.operations
+                .iter()
+                .filter_map(|op| op.greeting_keypair.as_ref())
+                .map(|keypair| keypair as &dyn Signer),
+        );
+        signers
+    }
+}
+
+/// Whether transactions load the manifest's accounts from a lookup table.
+pub enum LookupTableChoice {
+    /// Legacy transactions only
+    None,
+    /// Create a table for the run when there are enough accounts to matter
+    Auto,
+    Existing(Pubkey),
+}
+
+/// Where to read the manifest from, where to record results, how many
+/// transactions may be in flight at once and which lookup table to use.
+pub struct BatchOptions<'a> {
+    pub manifest_path: &'a str,
+    pub report_path: Option<&'a str>,
+    pub concurrency: usize,
+    pub lookup_table: LookupTableChoice,
+}
+
+pub async fn run(
+    client: &GreetingClient,
+    payer: &Keypair,
+    owner: &Keypair,
+    options: BatchOptions<'_>,
+) -> Result<()> {
+    let BatchOptions { manifest_path, report_path, concurrency, lookup_table } = options;
+    if concurrency == 0 {
+        bail!("Concurrency must be at least 1");
+    }
+
+    let report_path = report_path
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}.report.json", manifest_path));
+
+    let rows = read_manifest(manifest_path)?;
+    let book = AddressBook::load()?;
+    let mut report = read_report(&report_path)?;
+
+    let pending: Vec<(usize, ManifestRow)> = rows
+        .into_iter()
+        .enumerate()
+        .map(|(index, row)| (index + 1, row))
+        .filter(|(row, manifest_row)| {
+            !matches!(report.get(row), Some(done) if done.status == Status::Ok && done.op == manifest_row.op)
+        })
+        .collect();
+
+    println!("\nManifest: {}", manifest_path);
+    println!("Rows to process: {} ({} already succeeded)", pending.len(), report.values().filter(|r| r.status == Status::Ok).count());
+
+    let mut operations = Vec::with_capacity(pending.len());
+    for (row, manifest_row) in pending {
+        let op = manifest_row.op;
+        match build_operation(client.program_id(), &book, payer, owner, row, manifest_row) {
+            Ok(operation) => operations.push(operation),
+            Err(err) => {
+                println!("Row {}: {:#}", row, err);
+                report.insert(row, ReportRow {
+                    row,
+                    op,
+                    account: None,
+                    status: Status::Failed,
+                    signature: None,
+                    error: Some(format!("{:#}", err)),
+                });
+            }
+        }
+    }
+
+    // Tables this run creates, deactivated however it ends
+    let mut created_tables = Vec::new();
+    let outcome = async {
+        let lookup_tables = match lookup_table {
+            LookupTableChoice::None => Vec::new(),
+            LookupTableChoice::Existing(table) => vec![client.get_lookup_table(table).await?.account],
+            LookupTableChoice::Auto => {
+                // New greetings sign their `init`, and signers cannot be looked up
+                let mut addresses: Vec<Pubkey> = operations
+                    .iter()
+                    .filter(|op| op.greeting_keypair.is_none())
+                    .map(|op| op.greeting)
+                    .collect::<HashSet<_>>()
+                    .into_iter()
+                    .collect();
+                if addresses.len() < AUTO_LOOKUP_TABLE_MIN_ACCOUNTS {
+                    Vec::new()
+                } else {
+                    addresses.extend([client.program_id(), system_program::id()]);
+                    // A table holds at most 256 addresses; a transaction may load from several
+                    let mut lookup_tables = Vec::new();
+                    for chunk in addresses.chunks(LOOKUP_TABLE_MAX_ADDRESSES) {
+                        let (table, _) = client.create_lookup_table(payer, payer).await?;
+                        created_tables.push(table);
+                        client.extend_lookup_table(payer, payer, table, chunk).await?;
+                        println!("Created lookup table {} with {} address(es)", table, chunk.len());
+                        lookup_tables.push(client.get_lookup_table(table).await?.account);
+                    }
+                    lookup_tables
+                }
+            }
+        };
+
+        let batches = pack(operations, &payer.pubkey(), &lookup_tables);
+        let waves = into_waves(batches);
+        let transaction_count: usize = waves.iter().map(Vec::len).sum();
+        println!("Packed into {} transaction(s) across {} wave(s)", transaction_count, waves.len());
+
+        // Per-transaction progress would interleave across concurrent sends
+        let quiet = client.clone().with_send_config(SendConfig {
+            progress: None,
+            lookup_tables,
+            ..client.send_config().clone()
+        });
+        let mut submitted = 0;
+
+        for wave in waves {
+            let results = submit_wave(&quiet, payer, owner, &wave, concurrency).await;
+
+            for (batch, result) in wave.iter().zip(results) {
+                submitted += 1;
+                match &result {
+                    Ok(signature) => println!("[{}/{}] {} row(s) confirmed: {}", submitted, transaction_count, batch.operations.len(), signature),
+                    Err(err) => println!("[{}/{}] {} row(s) failed: {:#}", submitted, transaction_count, batch.operations.len(), err),
+                }
+
+                for operation in &batch.operations {
+                    report.insert(operation.row, ReportRow {
+                        row: operation.row,
+                        op: operation.op,
+                        account: Some(operation.greeting.to_string()),
+                        status: if result.is_ok() { Status::Ok } else { Status::Failed },
+                        signature: result.as_ref().ok().map(ToString::to_string),
+                        error: result.as_ref().err().map(|err| format!("{:#}", err)),
+                    });
+                }
+            }
+
+            // Persist after every wave so an interrupted run can still be resumed
+            write_report(&report_path, &report)?;
+        }
+        write_report(&report_path, &report)?;
+        anyhow::Ok(())
+    }
+    .await;
+
+    // Nothing else uses the tables; start their cool-down so the rent can be reclaimed
+    for table in created_tables {
+        match client.deactivate_lookup_table(payer, payer, table).await {
+            Ok(_) => println!("\nDeactivated lookup table {}; reclaim its rent later with `alt close`", table),
+            Err(err) => println!("\nFailed to deactivate lookup table {}: {}", table, err),
+        }
+    }
+    outcome?;
+
+    let failed = report.values().filter(|r| r.status == Status::Failed).count();
+    println!("\nReport written to {}", report_path);
+    println!("Succeeded: {}, failed: {}", report.len() - failed, failed);
+
+    if failed > 0 {
+        bail!("{} row(s) failed; re-run with the same report to retry them", failed);
+    }
+
+    Ok(())
+}
+
+fn read_manifest(path: &str) -> Result<Vec<ManifestRow>> {
+    let contents = fs::read_to_string(path).with_context(|| format!("Failed to read manifest {}", path))?;
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let rows = match extension.as_str() {
+        "csv" => csv::Reader::from_reader(contents.as_bytes())
+            .deserialize()
+            .collect::<Result<Vec<ManifestRow>, _>>()?,
+        "json" => serde_json::from_str(&contents)?,
+        "yaml" | "yml" => serde_yaml::from_str(&contents)?,
+        _ => bail!("Unsupported manifest format `{}` (expected .csv, .json, .yaml or .yml)", extension),
+    };
+
+    Ok(rows)
+}
+
+fn read_report(path: &str) -> Result<HashMap<usize, ReportRow>> {
+    if !Path::new(path).exists() {
+        return Ok(HashMap::new());
+    }
+
+    let contents = fs::read_to_string(path).with_context(|| format!("Failed to read report {}", path))?;
+    let rows: Vec<ReportRow> = serde_json::from_str(&contents)?;
+    Ok(rows.into_iter().map(|row| (row.row, row)).collect())
+}
+
+fn write_report(path: &str, report: &HashMap<usize, ReportRow>) -> Result<()> {
+    let mut rows: Vec<&ReportRow> = report.values().collect();
+    rows.sort_by_key(|row| row.row);
+    fs::write(path, serde_json::to_string_pretty(&rows)?)
+        .with_context(|| format!("Failed to write report {}", path))
+}
+
+fn build_operation(
+    program_id: Pubkey,
+    book: &AddressBook,
+    payer: &Keypair,
+    owner: &Keypair,
+    row: usize,
+    manifest_row: ManifestRow,
+) -> Result<Operation> {
+    let account = || -> Result<Pubkey> {
+        let account = manifest_row
+            .account
+            .as_deref()
+            .with_context(|| format!("`{:?}` requires an account", manifest_row.op))?;
+        book.resolve(account)
+    };
+
+    let (greeting, instruction, greeting_keypair) = match manifest_row.op {
+        Op::Init => {
+            let owner = match &manifest_row.owner {
+                Some(owner) => Pubkey::from_str(owner)?,
+                None => owner.pubkey(),
+            };
+            let greeting_keypair = Keypair::new();
+            let greeting = greeting_keypair.pubkey();
+            let instruction = instructions::initialize(program_id, payer.pubkey(), greeting, owner);
+            (greeting, instruction, Some(greeting_keypair))
+        }
+        Op::Update => {
+            // An empty message must be spelled out, not erase the greeting by omission
+            let message = manifest_row
+                .message
+                .as_deref()
+                .with_context(|| format!("`{:?}` requires a message", manifest_row.op))?;
+            if message.len() > MAX_MESSAGE_LEN {
+                bail!("Message too long! Max length: {}", MAX_MESSAGE_LEN);
+            }
+            let greeting = account()?;
+            (greeting, instructions::update_message(program_id, owner.pubkey(), greeting, message), None)
+        }
+        Op::Increment => {
+            let greeting = account()?;
+            (greeting, instructions::increment_only(program_id, owner.pubkey(), greeting), None)
+        }
+        Op::Close => {
+            let greeting = account()?;
+            (greeting, instructions::close(program_id, owner.pubkey(), greeting, payer.pubkey()), None)
+        }
+    };
+
+    Ok(Operation {
+        row,
+        op: manifest_row.op,
+        greeting,
+        instruction,
+        greeting_keypair,
+    })
+}
+
+/// Greedily packs operations, in manifest order, into transactions that stay
+/// under the packet size and account lock limits. Room is always reserved for
+/// the two compute budget instructions so that the limits hold whatever
+/// `SendConfig` adds.
+fn pack(operations: Vec<Operation>, payer: &Pubkey, lookup_tables: &[AddressLookupTableAccount]) -> Vec<Batch> {
+    let mut batches = Vec::new();
+    let mut current: Vec<Operation> = Vec::new();
+
+    for operation in operations {
+        current.push(operation);
+        if current.len() > 1 && !fits(&current, payer, lookup_tables) {
+            let overflow = current.pop().expect("just pushed");
+            batches.push(Batch { operations: std::mem::take(&mut current) });
+            current.push(overflow);
+        }
+    }
+    if !current.is_empty() {
+        batches.push(Batch { operations: current });
+    }
+
+    batches
+}
+
+fn fits(operations: &[Operation], payer: &Pubkey, lookup_tables: &[AddressLookupTableAccount]) -> bool {
+    let mut instructions = vec![
+        ComputeBudgetInstruction::set_compute_unit_price(0),
+        ComputeBudgetInstruction::set_compute_unit_limit(0),
+    ];
+    instructions.extend(operations.iter().map(|op| op.instruction.clone()));
+
+    let Ok(message) = compile_message(&instructions, payer, Hash::default(), lookup_tables) else {
+        return false;
+    };
+    let signatures = message.header().num_required_signatures as usize;
+    let accounts = message.static_account_keys().len()
+        + message
+            .address_table_lookups()
+            .map_or(0, |lookups| lookups.iter().map(|l| l.writable_indexes.len() + l.readonly_indexes.len()).sum());
+
+    // Compact-u16 signature count (one byte below 128) followed by the signatures
+    let size = 1 + signatures * 64 + message.serialize().len();
+    size <= PACKET_DATA_SIZE && accounts <= MAX_ACCOUNTS_PER_TRANSACTION
+}
+
+/// Splits batches into waves whose transactions touch disjoint greeting
+/// accounts. Transactions within a wave run concurrently; waves run in order,
+/// so operations on the same account keep their manifest ordering.
+fn into_waves(batches: Vec<Batch>) -> Vec<Vec<Batch>> {
+    let mut waves: Vec<Vec<Batch>> = Vec::new();
+    let mut touched: HashSet<Pubkey> = HashSet::new();
+
+    for batch in batches {
+        let accounts = batch.accounts();
+        if waves.is_empty() || !touched.is_disjoint(&accounts) {
+            waves.push(Vec::new());
+            touched.clear();
+        }
+        touched.extend(accounts);
+        waves.last_mut().expect("wave exists").push(batch);
+    }
+
+    waves
+}
+
+async fn submit_wave(
+    client: &GreetingClient,
+    payer: &Keypair,
+    owner: &Keypair,
+    wave: &[Batch],
+    concurrency: usize,
+) -> Vec<Result<String>> {
+    stream::iter(wave)
+        .map(|batch| async move {
+            let signature = client.send(&batch.instructions(), payer, &batch.signers(payer, owner)).await?;
+            Ok(signature.to_string())
+        })
+        .buffered(concurrency)
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{signer::SignerError, transaction::VersionedTransaction};
+
+    fn init(program_id: Pubkey, payer: &Keypair, owner: Pubkey, row: usize) -> Operation {
+        let greeting_keypair = Keypair::new();
+        let greeting = greeting_keypair.pubkey();
+        Operation {
+            row,
+            op: Op::Init,
+            greeting,
+            instruction: instructions::initialize(program_id, payer.pubkey(), greeting, owner),
+            greeting_keypair: Some(greeting_keypair),
+        }
+    }
+
+    fn increment(program_id: Pubkey, owner: &Keypair, row: usize) -> Operation {
+        let greeting = Pubkey::new_unique();
+        Operation {
+            row,
+            op: Op::Increment,
+            greeting,
+            instruction: instructions::increment_only(program_id, owner.pubkey(), greeting),
+            greeting_keypair: None,
+        }
+    }
+
+    fn row(op: Op, account: Option<&str>, message: Option<&str>) -> ManifestRow {
+        ManifestRow { op, account: account.map(str::to_string), owner: None, message: message.map(str::to_string) }
+    }
+
+    #[test]
+    fn update_rows_need_an_explicit_message() {
+        let (program_id, payer, owner) = (Pubkey::new_unique(), Keypair::new(), Keypair::new());
+        let book = AddressBook::default();
+        let account = Pubkey::new_unique().to_string();
+        let build = |manifest_row| build_operation(program_id, &book, &payer, &owner, 1, manifest_row);
+
+        let err = build(row(Op::Update, Some(&account), None)).err().expect("missing message rejected");
+        assert_eq!(err.to_string(), "`Update` requires a message");
+        let err = build(row(Op::Update, None, Some("Hi"))).err().expect("missing account rejected");
+        assert_eq!(err.to_string(), "`Update` requires an account");
+        build(row(Op::Update, Some(&account), Some(""))).expect("an explicit empty message is allowed");
+    }
+
+    /// Signs `batch` as `submit_wave` would.
+    fn sign(batch: &Batch, payer: &Keypair, owner: &Keypair) -> std::result::Result<VersionedTransaction, SignerError> {
+        let message = compile_message(&batch.instructions(), &payer.pubkey(), Hash::default(), &[]).expect("message compiles");
+        VersionedTransaction::try_new(message, &batch.signers(payer, owner))
+    }
+
+    #[test]
+    fn init_only_batches_are_signed_without_a_separate_owner() {
+        let program_id = Pubkey::new_unique();
+        let payer = Keypair::new();
+        let owner = Keypair::new();
+        let operations = (1..=3).map(|row| init(program_id, &payer, owner.pubkey(), row)).collect();
+
+        let batches = pack(operations, &payer.pubkey(), &[]);
+        assert_eq!(batches.len(), 1);
+        assert!(batches[0].signers(&payer, &owner).iter().all(|signer| signer.pubkey() != owner.pubkey()));
+        sign(&batches[0], &payer, &owner).expect("signed by the payer and the new greetings");
+    }
+
+    #[test]
+    fn batches_with_owner_instructions_are_signed_by_the_owner() {
+        let program_id = Pubkey::new_unique();
+        let payer = Keypair::new();
+        let owner = Keypair::new();
+        let operations = vec![init(program_id, &payer, owner.pubkey(), 1), increment(program_id, &owner, 2)];
+
+        let batches = pack(operations, &payer.pubkey(), &[]);
+        assert_eq!(batches.len(), 1);
+        sign(&batches[0], &payer, &owner).expect("signed by the payer, the owner and the new greeting");
+    }
+
+    #[test]
+    fn lookup_tables_fit_more_operations_per_transaction() {
+        let program_id = Pubkey::new_unique();
+        let payer = Keypair::new();
+        let owner = Keypair::new();
+        let increments = || -> Vec<Operation> { (1..=60).map(|row| increment(program_id, &owner, row)).collect() };
+
+        let legacy = pack(increments(), &payer.pubkey(), &[]);
+        let operations = increments();
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: operations.iter().map(|op| op.greeting).chain([program_id]).collect(),
+        };
+        let with_table = pack(operations, &payer.pubkey(), &[table]);
+
+        for batches in [&legacy, &with_table] {
+            assert_eq!(batches.iter().map(|batch| batch.operations.len()).sum::<usize>(), 60);
+        }
+        assert!(
+            with_table.len() < legacy.len(),
+            "{} transaction(s) with a table, {} without",
+            with_table.len(),
+            legacy.len()
+        );
+    }
+}
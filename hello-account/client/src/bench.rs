@@ -0,0 +1,236 @@
+use anyhow::{anyhow, bail, Result};
+use futures::stream::{self, StreamExt};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+use solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionEncoding};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+use tokio::time::sleep_until;
+
+use hello_account_client::{instructions, transaction::SendConfig, ClientError, GreetingClient};
+
+// Rates outside this range are typos: below it a run takes days, above it
+// pacing is meaningless next to RPC latency
+const MIN_RATE: f64 = 0.01;
+const MAX_RATE: f64 = 1_000_000.0;
+
+// Well under a slot, so that latencies are not rounded up to the poll
+const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+pub struct BenchOptions {
+    pub accounts: usize,
+    pub transactions: usize,
+    /// Target transactions per second
+    pub rate: f64,
+    /// Maximum number of transactions in flight
+    pub concurrency: usize,
+    /// Share of transactions that are `IncrementOnly` rather than `UpdateMessage`
+    pub increment_ratio: f64,
+}
+
+struct Outcome {
+    latency: Duration,
+    result: Result<Signature, ClientError>,
+}
+
+/// Provisions greeting accounts, sends a paced stream of updates and
+/// increments at them and reports throughput, latency, failures and compute.
+pub async fn run(client: &GreetingClient, payer: &Keypair, owner: &Keypair, options: BenchOptions) -> Result<()> {
+    let BenchOptions { accounts, transactions, rate, concurrency, increment_ratio } = options;
+    if accounts == 0 || transactions == 0 || concurrency == 0 {
+        bail!("--accounts, --transactions and --concurrency must be at least 1");
+    }
+    check_rate(rate).map_err(|err| anyhow!("--rate {}", err))?;
+    if !(0.0..=1.0).contains(&increment_ratio) {
+        bail!("--increment-ratio must be between 0 and 1");
+    }
+
+    // Per-transaction progress would interleave across concurrent sends
+    let quiet = client.clone().with_send_config(SendConfig {
+        progress: None,
+        status_poll_interval: STATUS_POLL_INTERVAL,
+        ..client.send_config().clone()
+    });
+
+    println!("\nProvisioning {} greeting account(s)...", accounts);
+    let provisioned: Vec<_> = stream::iter(0..accounts)
+        .map(|_| quiet.initialize(payer, owner.pubkey()))
+        .buffer_unordered(concurrency)
+        .collect()
+        .await;
+    let mut greetings: Vec<Pubkey> = Vec::with_capacity(accounts);
+    for initialized in provisioned {
+        greetings.push(initialized?.greeting);
+    }
+
+    println!(
+        "Sending {} transaction(s) at {} TPS with up to {} in flight...",
+        transactions, rate, concurrency
+    );
+    let start = Instant::now();
+    let interval = Duration::from_secs_f64(1.0 / rate);
+    let increments = (transactions as f64 * increment_ratio).round() as usize;
+
+    let outcomes: Vec<Outcome> = stream::iter(0..transactions)
+        .map(|index| {
+            let quiet = &quiet;
+            let greeting = greetings[index % greetings.len()];
+            // Spread increments evenly through the run rather than up front
+            let increment = (index + 1) * increments / transactions > index * increments / transactions;
+            async move {
+                sleep_until((start + interval.mul_f64(index as f64)).into()).await;
+                let label = format!("bench #{}", index);
+                let instruction = if increment {
+                    instructions::increment_only(quiet.program_id(), owner.pubkey(), greeting)
+                } else {
+                    instructions::update_message(quiet.program_id(), owner.pubkey(), greeting, &label)
+                };
+                // Increments of one greeting within a blockhash would otherwise
+                // be the same transaction, landing once but counted twice
+                let memo = spl_memo::build_memo(label.as_bytes(), &[]);
+                let sent = Instant::now();
+                let result = quiet.send(&[instruction, memo], payer, &[payer, owner]).await;
+                Outcome { latency: sent.elapsed(), result }
+            }
+        })
+        .buffer_unordered(concurrency)
+        .collect()
+        .await;
+    let elapsed = start.elapsed();
+
+    let signatures: Vec<Signature> = outcomes.iter().filter_map(|outcome| outcome.result.as_ref().ok().copied()).collect();
+    let mut latencies: Vec<Duration> = outcomes
+        .iter()
+        .filter(|outcome| outcome.result.is_ok())
+        .map(|outcome| outcome.latency)
+        .collect();
+    latencies.sort();
+
+    let mut failures: BTreeMap<String, usize> = BTreeMap::new();
+    for outcome in &outcomes {
+        if let Err(err) = &outcome.result {
+            *failures.entry(failure_kind(err)).or_default() += 1;
+        }
+    }
+
+    println!("\nFetching compute units of {} confirmed transaction(s)...", signatures.len());
+    let mut compute_units: Vec<u64> = stream::iter(&signatures)
+        .map(|signature| compute_units_consumed(client, *signature))
+        .buffer_unordered(concurrency)
+        .filter_map(|units| async move { units })
+        .collect()
+        .await;
+    compute_units.sort();
+
+    println!("\nResults over {:.2}s:", elapsed.as_secs_f64());
+    println!("  Confirmed: {} of {}", signatures.len(), transactions);
+    println!("  Achieved TPS: {:.1}", signatures.len() as f64 / elapsed.as_secs_f64());
+
+    if !latencies.is_empty() {
+        println!("\nConfirmation latency:");
+        for (label, percentile) in [("p50", 50.0), ("p90", 90.0), ("p99", 99.0), ("max", 100.0)] {
+            println!("  {}: {} ms", label, self::percentile(&latencies, percentile).as_millis());
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\nFailures:");
+        for (kind, count) in &failures {
+            println!("  {:>6}  {}", count, kind);
+        }
+    }
+
+    if !compute_units.is_empty() {
+        let mean = compute_units.iter().sum::<u64>() as f64 / compute_units.len() as f64;
+        println!("\nCompute units ({} sampled, memo included):", compute_units.len());
+        println!("  min: {}", compute_units[0]);
+        for (label, percentile) in [("p50", 50.0), ("p90", 90.0), ("p99", 99.0)] {
+            println!("  {}: {}", label, self::percentile(&compute_units, percentile));
+        }
+        println!("  max: {}", compute_units[compute_units.len() - 1]);
+        println!("  mean: {:.0}", mean);
+    }
+
+    Ok(())
+}
+
+/// Parses `--rate`, in transactions per second.
+pub fn parse_rate(s: &str) -> Result<f64, String> {
+    check_rate(s.parse().map_err(|err| format!("{}", err))?)
+}
+
+fn check_rate(rate: f64) -> Result<f64, String> {
+    if !(MIN_RATE..=MAX_RATE).contains(&rate) {
+        return Err(format!("must be between {} and {} transactions per second, got {}", MIN_RATE, MAX_RATE, rate));
+    }
+    Ok(rate)
+}
+
+/// Nearest-rank percentile of sorted, non-empty `values`.
+fn percentile<T: Copy>(values: &[T], percentile: f64) -> T {
+    let rank = (percentile / 100.0 * values.len() as f64).ceil() as usize;
+    values[rank.clamp(1, values.len()) - 1]
+}
+
+/// Groups failures by the program error that caused them where possible.
+fn failure_kind(err: &ClientError) -> String {
+    if let Some(hello_error) = err.hello_error() {
+        return format!("HelloError::{:?}", hello_error);
+    }
+    match err {
+        ClientError::TransactionFailed { error, .. } | ClientError::SimulationFailed { error, .. } => error.to_string(),
+        ClientError::Rpc(err) => match err.get_transaction_error() {
+            Some(error) => error.to_string(),
+            None => format!("RPC error: {}", err.kind()),
+        },
+        ClientError::ConfirmationTimeout { .. } => "Confirmation timeout".to_string(),
+        err => err.to_string(),
+    }
+}
+
+async fn compute_units_consumed(client: &GreetingClient, signature: Signature) -> Option<u64> {
+    let transaction = client
+        .rpc()
+        .get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(client.rpc().commitment()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await
+        .ok()?;
+
+    match transaction.transaction.meta?.compute_units_consumed {
+        OptionSerializer::Some(units) => Some(units),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_must_be_in_range() {
+        for input in ["0.01", "1", "50", "1e6"] {
+            assert!(parse_rate(input).is_ok(), "{}", input);
+        }
+        for input in ["", "fast", "0", "-1", "1e-30", "NaN", "inf", "1e7"] {
+            assert!(parse_rate(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn send_times_do_not_overflow() {
+        let interval = Duration::from_secs_f64(1.0 / parse_rate("0.01").unwrap());
+        let index = u32::MAX as usize + 1;
+        assert_eq!(interval.mul_f64(index as f64), Duration::from_secs(100) * u32::MAX + Duration::from_secs(100));
+    }
+}
@@ -0,0 +1,340 @@
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::RpcFilterType,
+};
+use solana_sdk::{
+    account::Account,
+    address_lookup_table::{
+        instruction::{close_lookup_table, create_lookup_table, deactivate_lookup_table, extend_lookup_table},
+        state::{AddressLookupTable, LookupTableMeta},
+        AddressLookupTableAccount,
+    },
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    message::Message,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+use std::{sync::Arc, time::Duration};
+
+use hello_account::state::{Greeting, MAX_MESSAGE_LEN};
+
+use crate::{
+    error::{ClientError, Result},
+    history::{fetch_history, HistoryEntry},
+    inspect::{inspect_account, Inspection},
+    instructions,
+    transaction::{send_instructions, SendConfig},
+};
+
+// Addresses per extend transaction, leaving room for compute budget instructions
+const LOOKUP_TABLE_EXTEND_CHUNK: usize = 20;
+
+// How often to check whether freshly extended addresses are usable
+const LOOKUP_TABLE_POLL_INTERVAL: Duration = Duration::from_millis(400);
+
+/// An address lookup table and its lifecycle state.
+#[derive(Debug, Clone)]
+pub struct LookupTable {
+    pub account: AddressLookupTableAccount,
+    pub meta: LookupTableMeta,
+}
+
+impl LookupTable {
+    pub fn is_deactivated(&self) -> bool {
+        self.meta.deactivation_slot != u64::MAX
+    }
+}
+
+/// A greeting account created by `GreetingClient::initialize`.
+#[derive(Debug)]
+pub struct InitializedGreeting {
+    pub greeting: Pubkey,
+    pub signature: Signature,
+}
+
+/// What `GreetingClient::initialize` will cost a payer, before any priority fee.
+#[derive(Debug, Clone, Copy)]
+pub struct InitializeCost {
+    /// Rent-exempt minimum for `Greeting::space()` bytes
+    pub rent: u64,
+    pub fee: u64,
+    pub balance: u64,
+}
+
+impl InitializeCost {
+    pub fn total(&self) -> u64 {
+        self.rent.saturating_add(self.fee)
+    }
+
+    pub fn shortfall(&self) -> u64 {
+        self.total().saturating_sub(self.balance)
+    }
+}
+
+/// Typed async access to a deployed hello-account program.
+///
+/// Cheap to clone; clones share the underlying RPC connection.
+#[derive(Clone)]
+pub struct GreetingClient {
+    rpc: Arc<RpcClient>,
+    program_id: Pubkey,
+    send_config: SendConfig,
+}
+
+impl GreetingClient {
+    pub fn new(rpc: RpcClient, program_id: Pubkey) -> Self {
+        Self::from_arc(Arc::new(rpc), program_id)
+    }
+
+    pub fn from_arc(rpc: Arc<RpcClient>, program_id: Pubkey) -> Self {
+        Self {
+            rpc,
+            program_id,
+            send_config: SendConfig::default(),
+        }
+    }
+
+    /// Connects to `rpc_url` at `confirmed` commitment.
+    pub fn connect(rpc_url: impl ToString, program_id: Pubkey) -> Self {
+        Self::new(
+            RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed()),
+            program_id,
+        )
+    }
+
+    pub fn with_send_config(mut self, send_config: SendConfig) -> Self {
+        self.send_config = send_config;
+        self
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    pub fn send_config(&self) -> &SendConfig {
+        &self.send_config
+    }
+
+    /// Creates a greeting account at a fresh address owned by `owner`.
+    pub async fn initialize(&self, payer: &dyn Signer, owner: Pubkey) -> Result<InitializedGreeting> {
+        let greeting_keypair = Keypair::new();
+        let signature = self.initialize_with_keypair(payer, &greeting_keypair, owner).await?;
+
+        Ok(InitializedGreeting {
+            greeting: greeting_keypair.pubkey(),
+            signature,
+        })
+    }
+
+    /// Creates a greeting account at the address of `greeting`.
+    pub async fn initialize_with_keypair(
+        &self,
+        payer: &dyn Signer,
+        greeting: &dyn Signer,
+        owner: Pubkey,
+    ) -> Result<Signature> {
+        let instruction = instructions::initialize(self.program_id, payer.pubkey(), greeting.pubkey(), owner);
+        self.send(&[instruction], payer, &[payer, greeting]).await
+    }
+
+    pub async fn initialize_cost(&self, payer: Pubkey, owner: Pubkey) -> Result<InitializeCost> {
+        // The greeting only needs to be a distinct signer for the fee
+        let instruction = instructions::initialize(self.program_id, payer, Keypair::new().pubkey(), owner);
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        let message = Message::new_with_blockhash(&[instruction], Some(&payer), &blockhash);
+
+        Ok(InitializeCost {
+            rent: self.rpc.get_minimum_balance_for_rent_exemption(Greeting::space()).await?,
+            fee: self.rpc.get_fee_for_message(&message).await?,
+            balance: self.rpc.get_balance(&payer).await?,
+        })
+    }
+
+    /// Requests an airdrop and waits for it to land. Only test clusters
+    /// serve airdrops.
+    pub async fn airdrop(&self, to: Pubkey, lamports: u64) -> Result<Signature> {
+        let signature = self.rpc.request_airdrop(&to, lamports).await?;
+        self.rpc.poll_for_signature(&signature).await?;
+
+        match self.rpc.get_signature_status(&signature).await? {
+            Some(Err(error)) => Err(ClientError::TransactionFailed { signature, error }),
+            _ => Ok(signature),
+        }
+    }
+
+    pub async fn update_message(
+        &self,
+        payer: &dyn Signer,
+        owner: &dyn Signer,
+        greeting: Pubkey,
+        message: &str,
+    ) -> Result<Signature> {
+        if message.len() > MAX_MESSAGE_LEN {
+            return Err(ClientError::MessageTooLong(message.len()));
+        }
+
+        let instruction = instructions::update_message(self.program_id, owner.pubkey(), greeting, message);
+        self.send(&[instruction], payer, &[payer, owner]).await
+    }
+
+    pub async fn increment(&self, payer: &dyn Signer, owner: &dyn Signer, greeting: Pubkey) -> Result<Signature> {
+        let instruction = instructions::increment_only(self.program_id, owner.pubkey(), greeting);
+        self.send(&[instruction], payer, &[payer, owner]).await
+    }
+
+    /// Closes `greeting`, sending its lamports to `destination`.
+    pub async fn close(
+        &self,
+        payer: &dyn Signer,
+        owner: &dyn Signer,
+        greeting: Pubkey,
+        destination: Pubkey,
+    ) -> Result<Signature> {
+        let instruction = instructions::close(self.program_id, owner.pubkey(), greeting, destination);
+        self.send(&[instruction], payer, &[payer, owner]).await
+    }
+
+    pub async fn get_greeting(&self, greeting: Pubkey) -> Result<Greeting> {
+        let account = self.get_raw_account(greeting).await?;
+
+        Greeting::load(&account.data).map_err(|source| ClientError::InvalidGreeting {
+            account: greeting,
+            source,
+        })
+    }
+
+    pub async fn get_raw_account(&self, address: Pubkey) -> Result<Account> {
+        self.rpc
+            .get_account_with_commitment(&address, self.rpc.commitment())
+            .await?
+            .value
+            .ok_or(ClientError::AccountNotFound(address))
+    }
+
+    /// Every account the program owns with the size of a greeting, raw, so
+    /// that callers decide how strictly to decode them.
+    pub async fn get_program_greetings(&self) -> Result<Vec<(Pubkey, Account)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::DataSize(Greeting::space() as u64)]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(self.rpc.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+
+        Ok(self.rpc.get_program_accounts_with_config(&self.program_id, config).await?)
+    }
+
+    /// Fetches `address` raw and diagnoses it as a greeting, without failing
+    /// on malformed or foreign data.
+    pub async fn inspect(&self, address: Pubkey) -> Result<Inspection> {
+        let account = self.get_raw_account(address).await?;
+        let rent_exempt_minimum = self.rpc.get_minimum_balance_for_rent_exemption(Greeting::space()).await?;
+
+        Ok(inspect_account(self.program_id, address, account, rent_exempt_minimum))
+    }
+
+    /// Transactions that touched `greeting`, newest first, with every
+    /// hello-account instruction in them decoded.
+    pub async fn history(&self, greeting: Pubkey, limit: Option<usize>) -> Result<Vec<HistoryEntry>> {
+        fetch_history(&self.rpc, self.program_id, greeting, limit).await
+    }
+
+    /// Creates an empty address lookup table controlled by `authority`.
+    pub async fn create_lookup_table(&self, payer: &dyn Signer, authority: &dyn Signer) -> Result<(Pubkey, Signature)> {
+        // The table address derives from a slot that must still be in SlotHashes;
+        // a table created moments ago may have taken this one, so wait for the next
+        let (instruction, table) = loop {
+            let recent_slot = self.rpc.get_slot_with_commitment(CommitmentConfig::finalized()).await?;
+            let (instruction, table) = create_lookup_table(authority.pubkey(), payer.pubkey(), recent_slot);
+            let existing = self.rpc.get_account_with_commitment(&table, CommitmentConfig::processed()).await?;
+            if existing.value.is_none() {
+                break (instruction, table);
+            }
+            tokio::time::sleep(LOOKUP_TABLE_POLL_INTERVAL).await;
+        };
+        let signature = self.send(&[instruction], payer, &[payer, authority]).await?;
+        Ok((table, signature))
+    }
+
+    /// Appends `addresses` to `table`, in as many transactions as it takes, and
+    /// returns once they are usable, which is from the slot after the last
+    /// extension.
+    pub async fn extend_lookup_table(
+        &self,
+        payer: &dyn Signer,
+        authority: &dyn Signer,
+        table: Pubkey,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Signature>> {
+        let mut signatures = Vec::new();
+        for chunk in addresses.chunks(LOOKUP_TABLE_EXTEND_CHUNK) {
+            let instruction = extend_lookup_table(table, authority.pubkey(), Some(payer.pubkey()), chunk.to_vec());
+            signatures.push(self.send(&[instruction], payer, &[payer, authority]).await?);
+        }
+
+        let last_extended_slot = self.get_lookup_table(table).await?.meta.last_extended_slot;
+        while self.rpc.get_slot().await? <= last_extended_slot {
+            tokio::time::sleep(LOOKUP_TABLE_POLL_INTERVAL).await;
+        }
+        Ok(signatures)
+    }
+
+    /// Starts the cool-down after which `table` can be closed. A deactivated
+    /// table can no longer be extended or used.
+    pub async fn deactivate_lookup_table(
+        &self,
+        payer: &dyn Signer,
+        authority: &dyn Signer,
+        table: Pubkey,
+    ) -> Result<Signature> {
+        let instruction = deactivate_lookup_table(table, authority.pubkey());
+        self.send(&[instruction], payer, &[payer, authority]).await
+    }
+
+    /// Deletes a deactivated table whose cool-down has passed, sending its
+    /// rent to `recipient`.
+    pub async fn close_lookup_table(
+        &self,
+        payer: &dyn Signer,
+        authority: &dyn Signer,
+        table: Pubkey,
+        recipient: Pubkey,
+    ) -> Result<Signature> {
+        let instruction = close_lookup_table(table, authority.pubkey(), recipient);
+        self.send(&[instruction], payer, &[payer, authority]).await
+    }
+
+    pub async fn get_lookup_table(&self, table: Pubkey) -> Result<LookupTable> {
+        let account = self.get_raw_account(table).await?;
+        let state = AddressLookupTable::deserialize(&account.data)
+            .map_err(|source| ClientError::InvalidLookupTable { account: table, source })?;
+
+        Ok(LookupTable {
+            account: AddressLookupTableAccount {
+                key: table,
+                addresses: state.addresses.to_vec(),
+            },
+            meta: state.meta,
+        })
+    }
+
+    /// Sends arbitrary instructions with this client's `SendConfig`.
+    pub async fn send(
+        &self,
+        instructions: &[Instruction],
+        payer: &dyn Signer,
+        signers: &[&dyn Signer],
+    ) -> Result<Signature> {
+        send_instructions(&self.rpc, instructions, payer, signers, &self.send_config).await
+    }
+}
@@ -0,0 +1,171 @@
+use std::{fmt, str::FromStr};
+
+const LOCALNET_RPC_URL: &str = "http://localhost:8899";
+const DEVNET_RPC_URL: &str = "https://api.devnet.solana.com";
+const TESTNET_RPC_URL: &str = "https://api.testnet.solana.com";
+const MAINNET_BETA_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+
+/// A cluster named by moniker, or any other RPC endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Cluster {
+    Localnet,
+    Devnet,
+    Testnet,
+    MainnetBeta,
+    Custom(String),
+}
+
+impl Cluster {
+    pub fn rpc_url(&self) -> &str {
+        match self {
+            Self::Localnet => LOCALNET_RPC_URL,
+            Self::Devnet => DEVNET_RPC_URL,
+            Self::Testnet => TESTNET_RPC_URL,
+            Self::MainnetBeta => MAINNET_BETA_RPC_URL,
+            Self::Custom(url) => url,
+        }
+    }
+
+    /// The pubsub endpoint paired with `rpc_url`: same host and path over
+    /// ws(s), on the next port when the RPC port is explicit, as validators
+    /// bind it.
+    pub fn ws_url(&self) -> String {
+        let rpc_url = self.rpc_url();
+        let (scheme, rest) = match rpc_url.split_once("://") {
+            Some(("https", rest)) => ("wss", rest),
+            Some((_, rest)) => ("ws", rest),
+            None => ("ws", rpc_url),
+        };
+        let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+
+        // An IPv6 host without a port leaves `1]` or similar, which is no port
+        let port = authority
+            .rsplit_once(':')
+            .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)));
+        let authority = match port {
+            Some((host, port)) => format!("{}:{}", host, port.saturating_add(1)),
+            None => authority.to_string(),
+        };
+        format!("{}://{}{}", scheme, authority, path)
+    }
+
+    /// A validator on this machine, where airdrops are free and unlimited.
+    pub fn is_local(&self) -> bool {
+        match self {
+            Self::Localnet => true,
+            Self::Custom(url) => {
+                let host = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+                ["localhost", "127.0.0.1", "0.0.0.0", "[::1]"]
+                    .iter()
+                    .any(|local| host.strip_prefix(local).is_some_and(|rest| rest.is_empty() || rest.starts_with([':', '/'])))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Accepts `localnet`, `devnet`, `testnet`, `mainnet-beta`, their first
+/// letters as with the Solana CLI, or a URL.
+impl FromStr for Cluster {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "localnet" | "l" => Ok(Self::Localnet),
+            "devnet" | "d" => Ok(Self::Devnet),
+            "testnet" | "t" => Ok(Self::Testnet),
+            "mainnet-beta" | "m" => Ok(Self::MainnetBeta),
+            url if url.starts_with("http://") || url.starts_with("https://") => Ok(Self::Custom(url.to_string())),
+            _ => Err(format!(
+                "expected localnet, devnet, testnet, mainnet-beta or an http(s) URL, got `{}`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Localnet => write!(f, "localnet"),
+            Self::Devnet => write!(f, "devnet"),
+            Self::Testnet => write!(f, "testnet"),
+            Self::MainnetBeta => write!(f, "mainnet-beta"),
+            Self::Custom(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cluster(input: &str) -> Cluster {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn monikers_and_urls_parse() {
+        let cases = [
+            ("localnet", Cluster::Localnet),
+            ("l", Cluster::Localnet),
+            ("devnet", Cluster::Devnet),
+            ("d", Cluster::Devnet),
+            ("testnet", Cluster::Testnet),
+            ("t", Cluster::Testnet),
+            ("mainnet-beta", Cluster::MainnetBeta),
+            ("m", Cluster::MainnetBeta),
+            ("http://localhost:8899", Cluster::Custom("http://localhost:8899".to_string())),
+            ("https://rpc.example.com/key", Cluster::Custom("https://rpc.example.com/key".to_string())),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<Cluster>(), Ok(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn unknown_clusters_are_rejected() {
+        for input in ["", "local", "mainnet", "Devnet", "localhost:8899", "ws://localhost:8900"] {
+            assert!(input.parse::<Cluster>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn websocket_urls_follow_the_rpc_url() {
+        let cases = [
+            ("localnet", "ws://localhost:8900"),
+            ("devnet", "wss://api.devnet.solana.com"),
+            ("testnet", "wss://api.testnet.solana.com"),
+            ("mainnet-beta", "wss://api.mainnet-beta.solana.com"),
+            ("http://127.0.0.1:8899", "ws://127.0.0.1:8900"),
+            ("https://rpc.example.com/Key", "wss://rpc.example.com/Key"),
+            ("https://rpc.example.com:8443/v1?key=abc", "wss://rpc.example.com:8444/v1?key=abc"),
+            ("http://[::1]:8899", "ws://[::1]:8900"),
+            ("http://[::1]/rpc", "ws://[::1]/rpc"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(cluster(input).ws_url(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn only_this_machine_is_local() {
+        let cases = [
+            ("localnet", true),
+            ("http://localhost:8899", true),
+            ("http://127.0.0.1:8899", true),
+            ("http://0.0.0.0", true),
+            ("http://[::1]:8899/", true),
+            ("https://localhost/rpc", true),
+            ("devnet", false),
+            ("mainnet-beta", false),
+            ("http://localhost.example.com:8899", false),
+            ("http://127.0.0.10:8899", false),
+            ("https://rpc.example.com/localhost", false),
+        ];
+        for (input, local) in cases {
+            let cluster: Cluster = input.parse().unwrap();
+            assert_eq!(cluster.is_local(), local, "{}", input);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env, fs, path::PathBuf};
+
+use hello_account_client::Cluster;
+
+// Overrides the default location, e.g. to keep test runs isolated
+const CONFIG_DIR_ENV: &str = "HELLO_ACCOUNT_CONFIG_DIR";
+const DEFAULT_CONFIG_DIR: &str = "~/.config/hello-account";
+const CONFIG_FILE: &str = "config.json";
+
+/// Directory holding the client's local state (shell history, address book).
+pub fn config_dir() -> PathBuf {
+    match env::var(CONFIG_DIR_ENV) {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(shellexpand::tilde(DEFAULT_CONFIG_DIR).as_ref()),
+    }
+}
+
+/// Settings that `deploy` and `upgrade` record for later runs, kept per
+/// cluster: a program deployed to one cluster does not exist on the others.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ClientConfig {
+    /// Keyed by `cluster_key`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    clusters: BTreeMap<String, ClusterConfig>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ClusterConfig {
+    /// Program deployed by `deploy`, used instead of the built-in id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_id: Option<String>,
+
+    /// Buffer of an unfinished deploy or upgrade, resumed by the next one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_buffer: Option<PendingBuffer>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PendingBuffer {
+    pub address: String,
+    /// SHA-256 of the program being written, so a rebuilt `.so` starts over
+    pub program_hash: String,
+}
+
+impl ClientConfig {
+    fn path() -> PathBuf {
+        config_dir().join(CONFIG_FILE)
+    }
+
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        fs::create_dir_all(config_dir())?;
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn cluster(&self, cluster: &Cluster) -> Option<&ClusterConfig> {
+        self.clusters.get(&cluster_key(cluster))
+    }
+
+    pub fn cluster_mut(&mut self, cluster: &Cluster) -> &mut ClusterConfig {
+        self.clusters.entry(cluster_key(cluster)).or_default()
+    }
+}
+
+/// The cluster's RPC URL with the scheme and host lowercased and no trailing
+/// slash, so that a moniker and its URL share settings.
+fn cluster_key(cluster: &Cluster) -> String {
+    let url = cluster.rpc_url().trim_end_matches('/');
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    format!("{}://{}{}", scheme.to_ascii_lowercase(), authority.to_ascii_lowercase(), path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cluster(input: &str) -> Cluster {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn spellings_of_one_endpoint_share_a_key() {
+        for input in ["l", "http://localhost:8899", "http://localhost:8899/"] {
+            assert_eq!(cluster_key(&cluster(input)), cluster_key(&Cluster::Localnet), "{}", input);
+        }
+        let shouting = Cluster::Custom("HTTP://LocalHost:8899".to_string());
+        assert_eq!(cluster_key(&shouting), cluster_key(&Cluster::Localnet));
+        assert_eq!(cluster_key(&cluster("https://api.devnet.solana.com/")), cluster_key(&Cluster::Devnet));
+        // Paths may carry case-sensitive API keys
+        assert_ne!(cluster_key(&cluster("https://rpc.example.com/Key")), cluster_key(&cluster("https://rpc.example.com/key")));
+    }
+
+    #[test]
+    fn clusters_keep_their_own_program_id() {
+        let mut config = ClientConfig::default();
+        config.cluster_mut(&Cluster::Localnet).program_id = Some("local".to_string());
+
+        assert_eq!(config.cluster(&cluster("http://localhost:8899")).and_then(|c| c.program_id.as_deref()), Some("local"));
+        assert!(config.cluster(&Cluster::Devnet).is_none());
+        assert!(config.cluster(&Cluster::MainnetBeta).is_none());
+    }
+}
@@ -0,0 +1,393 @@
+use anyhow::{anyhow, bail, Context, Result};
+use futures::stream::{self, StreamExt};
+use solana_sdk::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    compute_budget::ComputeBudgetInstruction,
+    hash::hash,
+    message::Message,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, write_keypair_file, Keypair, Signer},
+};
+use std::{fs, path::Path};
+
+use hello_account_client::{transaction::SendConfig, ClientError, Cluster, GreetingClient};
+
+use crate::config::{ClientConfig, PendingBuffer};
+
+/// What happens to the upgrade authority once the program is written.
+pub enum AuthorityChange {
+    Keep,
+    Transfer(Pubkey),
+    /// Make the program immutable
+    Final,
+}
+
+pub struct DeployOptions<'a> {
+    /// Where the program id and any pending buffer are recorded
+    pub cluster: &'a Cluster,
+    pub program_path: &'a str,
+    /// Defaults to `<name>-keypair.json` next to the `.so`, created if missing
+    pub program_keypair_path: Option<&'a str>,
+    /// Room reserved for future upgrades; defaults to twice the program size
+    pub max_len: Option<usize>,
+    pub authority: &'a Keypair,
+    pub authority_change: AuthorityChange,
+    pub concurrency: usize,
+}
+
+pub struct UpgradeOptions<'a> {
+    pub cluster: &'a Cluster,
+    pub program_path: &'a str,
+    pub program_id: Pubkey,
+    pub authority: &'a Keypair,
+    pub authority_change: AuthorityChange,
+    pub concurrency: usize,
+}
+
+/// Deploys a new program through a buffer and records its id in the client
+/// config for the cluster.
+pub async fn deploy(client: &GreetingClient, payer: &Keypair, options: DeployOptions<'_>) -> Result<Pubkey> {
+    let program = read_program(options.program_path)?;
+    let program_keypair = load_program_keypair(options.program_path, options.program_keypair_path)?;
+    let program_id = program_keypair.pubkey();
+
+    let max_len = options.max_len.unwrap_or(program.len() * 2);
+    if max_len < program.len() {
+        bail!("--max-len {} is smaller than the program ({} bytes)", max_len, program.len());
+    }
+    if client.rpc().get_account_with_commitment(&program_id, client.rpc().commitment()).await?.value.is_some() {
+        bail!("Program {} already exists; use `upgrade` instead", program_id);
+    }
+
+    println!("\nDeploying {} ({} bytes) as {}", options.program_path, program.len(), program_id);
+    let buffer = write_buffer(client, options.cluster, payer, options.authority, &program, options.concurrency).await?;
+
+    let lamports = client
+        .rpc()
+        .get_minimum_balance_for_rent_exemption(UpgradeableLoaderState::size_of_program())
+        .await?;
+    let instructions = bpf_loader_upgradeable::deploy_with_max_program_len(
+        &payer.pubkey(),
+        &program_id,
+        &buffer,
+        &options.authority.pubkey(),
+        lamports,
+        max_len,
+    )?;
+    let signature = client.send(&instructions, payer, &[payer, &program_keypair, options.authority]).await?;
+
+    println!("Deployed! Signature: {}", signature);
+    println!("Program ID: {}", program_id);
+
+    // The buffer was consumed by the deploy
+    let mut config = ClientConfig::load()?;
+    let cluster_config = config.cluster_mut(options.cluster);
+    cluster_config.pending_buffer = None;
+    cluster_config.program_id = Some(program_id.to_string());
+    config.save()?;
+    println!("Recorded {} as the client's program id on {}", program_id, options.cluster);
+
+    change_authority(client, payer, program_id, options.authority, options.authority_change).await?;
+    Ok(program_id)
+}
+
+/// Replaces the bytes of a deployed program, extending its data account
+/// first when the new build no longer fits.
+pub async fn upgrade(client: &GreetingClient, payer: &Keypair, options: UpgradeOptions<'_>) -> Result<()> {
+    let UpgradeOptions { cluster, program_path, program_id, authority, authority_change, concurrency } = options;
+    let program = read_program(program_path)?;
+
+    let (_, programdata) = fetch_programdata(client, program_id).await?;
+    let UpgradeableLoaderState::ProgramData { upgrade_authority_address, .. } = bincode::deserialize(&programdata)? else {
+        bail!("Program data of {} is not initialized", program_id);
+    };
+    match upgrade_authority_address {
+        None => bail!("Program {} is immutable", program_id),
+        Some(current) if current != authority.pubkey() => {
+            bail!("Upgrade authority of {} is {}, not {}", program_id, current, authority.pubkey())
+        }
+        Some(_) => {}
+    }
+
+    println!("\nUpgrading {} with {} ({} bytes)", program_id, program_path, program.len());
+
+    let capacity = programdata.len() - UpgradeableLoaderState::size_of_programdata_metadata();
+    if program.len() > capacity {
+        let additional_bytes = (program.len() - capacity) as u32;
+        println!("Extending program data by {} bytes...", additional_bytes);
+        let instruction = bpf_loader_upgradeable::extend_program(&program_id, Some(&payer.pubkey()), additional_bytes);
+        client.send(&[instruction], payer, &[payer]).await?;
+    }
+
+    let buffer = write_buffer(client, cluster, payer, authority, &program, concurrency).await?;
+
+    // The buffer's lamports are refunded to the payer
+    let instruction = bpf_loader_upgradeable::upgrade(&program_id, &buffer, &authority.pubkey(), &payer.pubkey());
+    let signature = client.send(&[instruction], payer, &[payer, authority]).await?;
+    println!("Upgraded! Signature: {}", signature);
+
+    let mut config = ClientConfig::load()?;
+    config.cluster_mut(cluster).pending_buffer = None;
+    config.save()?;
+
+    change_authority(client, payer, program_id, authority, authority_change).await
+}
+
+pub async fn change_authority(
+    client: &GreetingClient,
+    payer: &Keypair,
+    program_id: Pubkey,
+    authority: &Keypair,
+    change: AuthorityChange,
+) -> Result<()> {
+    let new_authority = match change {
+        AuthorityChange::Keep => return Ok(()),
+        AuthorityChange::Transfer(new_authority) => Some(new_authority),
+        AuthorityChange::Final => None,
+    };
+
+    let instruction = bpf_loader_upgradeable::set_upgrade_authority(&program_id, &authority.pubkey(), new_authority.as_ref());
+    let signature = client.send(&[instruction], payer, &[payer, authority]).await?;
+
+    match new_authority {
+        Some(new_authority) => println!("Upgrade authority transferred to {}: {}", new_authority, signature),
+        None => println!("Program is now immutable: {}", signature),
+    }
+    Ok(())
+}
+
+/// Compares the deployed bytes of `program_id` with a local build, ignoring
+/// the zero padding both may carry after the ELF.
+pub async fn verify(client: &GreetingClient, program_id: Pubkey, program_path: &str) -> Result<()> {
+    let local = read_program(program_path)?;
+    let (programdata_address, programdata) = fetch_programdata(client, program_id).await?;
+    let UpgradeableLoaderState::ProgramData { slot, upgrade_authority_address } = bincode::deserialize(&programdata)? else {
+        bail!("Program data of {} is not initialized", program_id);
+    };
+
+    let deployed = trim_padding(&programdata[UpgradeableLoaderState::size_of_programdata_metadata()..]);
+    let local = trim_padding(&local);
+    // Hex, as `sha256sum` prints it
+    let deployed_hash = hex_sha256(deployed);
+    let local_hash = hex_sha256(local);
+
+    println!("\nProgram: {}", program_id);
+    println!("Program data: {}", programdata_address);
+    match upgrade_authority_address {
+        Some(authority) => println!("Upgrade authority: {}", authority),
+        None => println!("Upgrade authority: none (immutable)"),
+    }
+    println!("Last deployed slot: {}", slot);
+    println!("Deployed: {} bytes, sha256 {}", deployed.len(), deployed_hash);
+    println!("Local:    {} bytes, sha256 {} ({})", local.len(), local_hash, program_path);
+
+    if deployed_hash != local_hash {
+        let offset = deployed
+            .iter()
+            .zip(local)
+            .position(|(deployed, local)| deployed != local)
+            .unwrap_or(deployed.len().min(local.len()));
+        bail!("Mismatch: deployed program differs from {} starting at byte {}", program_path, offset);
+    }
+
+    println!("Match: the deployed program is {}", program_path);
+    Ok(())
+}
+
+fn hex_sha256(bytes: &[u8]) -> String {
+    hash(bytes).to_bytes().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn trim_padding(program: &[u8]) -> &[u8] {
+    let len = program.iter().rposition(|&byte| byte != 0).map_or(0, |last| last + 1);
+    &program[..len]
+}
+
+/// The program account's `ProgramData` address and that account's data.
+pub async fn fetch_programdata(client: &GreetingClient, program_id: Pubkey) -> Result<(Pubkey, Vec<u8>)> {
+    let program = client.get_raw_account(program_id).await?;
+    if program.owner != bpf_loader_upgradeable::id() {
+        bail!("{} is owned by {}, not the upgradeable loader", program_id, program.owner);
+    }
+    let UpgradeableLoaderState::Program { programdata_address } = bincode::deserialize(&program.data)? else {
+        bail!("{} is not a program account", program_id);
+    };
+
+    let programdata = client.get_raw_account(programdata_address).await?;
+    Ok((programdata_address, programdata.data))
+}
+
+fn read_program(path: &str) -> Result<Vec<u8>> {
+    let program = fs::read(path).with_context(|| format!("Failed to read program {}", path))?;
+    if program.is_empty() {
+        bail!("Program {} is empty", path);
+    }
+    Ok(program)
+}
+
+/// Loads the program keypair, or creates it next to the `.so` as
+/// `cargo build-sbf` would.
+fn load_program_keypair(program_path: &str, keypair_path: Option<&str>) -> Result<Keypair> {
+    if let Some(keypair_path) = keypair_path {
+        return read_keypair_file(keypair_path).map_err(|err| anyhow!("Failed to read program keypair {}: {}", keypair_path, err));
+    }
+
+    let program_path = Path::new(program_path);
+    let stem = program_path
+        .file_stem()
+        .with_context(|| format!("{} has no file name", program_path.display()))?
+        .to_string_lossy();
+    let keypair_path = program_path.with_file_name(format!("{}-keypair.json", stem));
+
+    if keypair_path.exists() {
+        return read_keypair_file(&keypair_path)
+            .map_err(|err| anyhow!("Failed to read program keypair {}: {}", keypair_path.display(), err));
+    }
+
+    let keypair = Keypair::new();
+    write_keypair_file(&keypair, &keypair_path)
+        .map_err(|err| anyhow!("Failed to write program keypair {}: {}", keypair_path.display(), err))?;
+    println!("Created program keypair {}", keypair_path.display());
+    Ok(keypair)
+}
+
+/// Writes `program` into a loader buffer owned by `authority` and returns
+/// its address. A buffer left by an earlier failed run for the same bytes is
+/// reused, and only chunks that differ from what it holds are written.
+async fn write_buffer(
+    client: &GreetingClient,
+    cluster: &Cluster,
+    payer: &Keypair,
+    authority: &Keypair,
+    program: &[u8],
+    concurrency: usize,
+) -> Result<Pubkey> {
+    if concurrency == 0 {
+        bail!("Concurrency must be at least 1");
+    }
+
+    let program_hash = hash(program).to_string();
+    let mut config = ClientConfig::load()?;
+
+    let pending = config.cluster(cluster).and_then(|cluster_config| cluster_config.pending_buffer.as_ref());
+    let buffer = match resumable_buffer(client, pending, &program_hash, authority, program.len()).await? {
+        Some(buffer) => {
+            println!("Resuming buffer {}", buffer);
+            buffer
+        }
+        None => {
+            let buffer_keypair = Keypair::new();
+            let buffer = buffer_keypair.pubkey();
+            let lamports = client
+                .rpc()
+                .get_minimum_balance_for_rent_exemption(UpgradeableLoaderState::size_of_buffer(program.len()))
+                .await?;
+            let instructions =
+                bpf_loader_upgradeable::create_buffer(&payer.pubkey(), &buffer, &authority.pubkey(), lamports, program.len())?;
+
+            // Recorded first so that any failure from here on can resume
+            config.cluster_mut(cluster).pending_buffer = Some(PendingBuffer {
+                address: buffer.to_string(),
+                program_hash,
+            });
+            config.save()?;
+
+            client.send(&instructions, payer, &[payer, &buffer_keypair, authority]).await?;
+            println!("Created buffer {}", buffer);
+            buffer
+        }
+    };
+
+    let written = client.get_raw_account(buffer).await?.data;
+    let written = &written[UpgradeableLoaderState::size_of_buffer_metadata()..];
+    let chunk_size = write_chunk_size(payer, authority, buffer);
+
+    let chunk_count = program.len().div_ceil(chunk_size);
+    let writes: Vec<_> = program
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(index, chunk)| (index * chunk_size, chunk))
+        .filter(|&(offset, chunk)| written[offset..offset + chunk.len()] != *chunk)
+        .map(|(offset, chunk)| bpf_loader_upgradeable::write(&buffer, &authority.pubkey(), offset as u32, chunk.to_vec()))
+        .collect();
+    println!("Writing {} of {} chunk(s) of {} bytes", writes.len(), chunk_count, chunk_size);
+
+    // Per-transaction progress would interleave across concurrent sends
+    let quiet = client.clone().with_send_config(SendConfig {
+        progress: None,
+        ..client.send_config().clone()
+    });
+    let total = writes.len();
+    let results: Vec<Result<_, ClientError>> = stream::iter(writes)
+        .map(|instruction| {
+            let quiet = &quiet;
+            async move { quiet.send(&[instruction], payer, &[payer, authority]).await }
+        })
+        .buffered(concurrency)
+        .collect()
+        .await;
+
+    let mut failed = 0;
+    for (index, result) in results.iter().enumerate() {
+        match result {
+            Ok(signature) => println!("[{}/{}] {}", index + 1, total, signature),
+            Err(err) => {
+                failed += 1;
+                println!("[{}/{}] failed: {}", index + 1, total, err);
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{} of {} chunk(s) failed; rerun the same command to resume buffer {}", failed, total, buffer);
+    }
+
+    Ok(buffer)
+}
+
+/// The pending buffer from the config, if it still exists on chain, holds
+/// the same program and is writable by `authority`.
+async fn resumable_buffer(
+    client: &GreetingClient,
+    pending: Option<&PendingBuffer>,
+    program_hash: &str,
+    authority: &Keypair,
+    program_len: usize,
+) -> Result<Option<Pubkey>> {
+    let Some(pending) = pending.filter(|pending| pending.program_hash == program_hash) else {
+        return Ok(None);
+    };
+    let buffer: Pubkey = pending.address.parse()?;
+
+    let account = match client.get_raw_account(buffer).await {
+        Ok(account) => account,
+        Err(ClientError::AccountNotFound(_)) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    if account.owner != bpf_loader_upgradeable::id()
+        || account.data.len() != UpgradeableLoaderState::size_of_buffer(program_len)
+    {
+        return Ok(None);
+    }
+
+    Ok(match bincode::deserialize(&account.data) {
+        Ok(UpgradeableLoaderState::Buffer { authority_address }) if authority_address == Some(authority.pubkey()) => Some(buffer),
+        _ => None,
+    })
+}
+
+/// Largest chunk whose `Write` transaction, with room for the compute budget
+/// instructions the sender may add, still fits in a packet.
+fn write_chunk_size(payer: &Keypair, authority: &Keypair, buffer: Pubkey) -> usize {
+    let instructions = [
+        ComputeBudgetInstruction::set_compute_unit_price(0),
+        ComputeBudgetInstruction::set_compute_unit_limit(0),
+        bpf_loader_upgradeable::write(&buffer, &authority.pubkey(), 0, Vec::new()),
+    ];
+    let message = Message::new(&instructions, Some(&payer.pubkey()));
+    let signatures = message.header.num_required_signatures as usize;
+    let size = 1 + signatures * 64 + message.serialize().len();
+
+    // The instruction data length prefix grows to two bytes once data is added
+    PACKET_DATA_SIZE - size - 1
+}
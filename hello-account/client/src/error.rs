@@ -0,0 +1,91 @@
+use solana_client::client_error::ClientError as RpcClientError;
+use solana_sdk::{
+    instruction::InstructionError,
+    message::CompileError,
+    pubkey::Pubkey,
+    signature::{ParseSignatureError, Signature},
+    signer::SignerError,
+    transaction::TransactionError,
+};
+use std::time::Duration;
+use thiserror::Error;
+
+use hello_account::{error::HelloError, state::MAX_MESSAGE_LEN};
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error(transparent)]
+    Rpc(#[from] RpcClientError),
+
+    #[error(transparent)]
+    Signer(#[from] SignerError),
+
+    #[error("Failed to compile message: {0}")]
+    Compile(#[from] CompileError),
+
+    #[error("Transaction {signature} failed: {error}")]
+    TransactionFailed {
+        signature: Signature,
+        error: TransactionError,
+    },
+
+    #[error("Transaction not confirmed within {}s ({} signature(s) sent)", timeout.as_secs(), signatures.len())]
+    ConfirmationTimeout {
+        signatures: Vec<Signature>,
+        timeout: Duration,
+    },
+
+    #[error("Simulation failed: {error}")]
+    SimulationFailed {
+        error: TransactionError,
+        logs: Vec<String>,
+    },
+
+    #[error("RPC node did not report consumed compute units")]
+    MissingUnitsConsumed,
+
+    #[error("Message too long: {0} bytes (max {MAX_MESSAGE_LEN})")]
+    MessageTooLong(usize),
+
+    #[error("RPC node returned an invalid signature `{signature}`: {source}")]
+    InvalidSignature {
+        signature: String,
+        source: ParseSignatureError,
+    },
+
+    #[error("Account {0} not found")]
+    AccountNotFound(Pubkey),
+
+    #[error("Account {account} is not a valid greeting: {source}")]
+    InvalidGreeting {
+        account: Pubkey,
+        source: std::io::Error,
+    },
+
+    #[error("Account {account} is not a valid address lookup table: {source}")]
+    InvalidLookupTable {
+        account: Pubkey,
+        source: InstructionError,
+    },
+}
+
+impl ClientError {
+    /// The program error that rejected the transaction, if it was one of ours.
+    pub fn hello_error(&self) -> Option<HelloError> {
+        match self {
+            Self::TransactionFailed { error, .. } | Self::SimulationFailed { error, .. } => hello_error(error),
+            Self::Rpc(err) => err.get_transaction_error().as_ref().and_then(hello_error),
+            _ => None,
+        }
+    }
+}
+
+/// Maps `InstructionError::Custom` codes back to `HelloError`.
+pub fn hello_error(error: &TransactionError) -> Option<HelloError> {
+    match error {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => HelloError::try_from(*code).ok(),
+        _ => None,
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
@@ -0,0 +1,298 @@
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{
+    clock::{Slot, UnixTimestamp},
+    instruction::CompiledInstruction,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::TransactionError,
+};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, UiInnerInstructions, UiInstruction, UiLoadedAddresses, UiTransactionEncoding,
+};
+use std::str::FromStr;
+
+use hello_account::{error::HelloError, instruction::HelloInstruction};
+
+use crate::error::{hello_error, ClientError, Result};
+
+// Largest page `getSignaturesForAddress` will return
+const SIGNATURE_PAGE_SIZE: usize = 1000;
+
+/// A hello-account instruction found in a transaction, with its accounts
+/// resolved to addresses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GreetingOperation {
+    Initialize {
+        payer: Pubkey,
+        greeting: Pubkey,
+        owner: Pubkey,
+    },
+    UpdateMessage {
+        owner: Pubkey,
+        greeting: Pubkey,
+        message: String,
+    },
+    IncrementOnly {
+        owner: Pubkey,
+        greeting: Pubkey,
+    },
+    Close {
+        owner: Pubkey,
+        greeting: Pubkey,
+        destination: Pubkey,
+    },
+    /// Data that `HelloInstruction::unpack` rejects
+    Undecodable { data: Vec<u8> },
+}
+
+/// One program instruction within a transaction. `inner` is set when the
+/// program was reached through a CPI from top-level instruction `index`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    pub index: u8,
+    pub inner: bool,
+    pub operation: GreetingOperation,
+}
+
+/// A transaction that touched the greeting account.
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub signature: Signature,
+    pub slot: Slot,
+    pub block_time: Option<UnixTimestamp>,
+    pub error: Option<TransactionError>,
+    pub instructions: Vec<DecodedInstruction>,
+}
+
+impl HistoryEntry {
+    /// The program error that failed the transaction, if it was one of ours.
+    pub fn hello_error(&self) -> Option<HelloError> {
+        self.error.as_ref().and_then(hello_error)
+    }
+}
+
+/// Walks the signatures of `address` newest first, up to `limit`, and decodes
+/// every hello-account instruction in them.
+pub async fn fetch_history(
+    client: &RpcClient,
+    program_id: Pubkey,
+    address: Pubkey,
+    limit: Option<usize>,
+) -> Result<Vec<HistoryEntry>> {
+    let mut entries = Vec::new();
+    let mut before = None;
+
+    if limit == Some(0) {
+        return Ok(entries);
+    }
+
+    loop {
+        let remaining = limit.map_or(SIGNATURE_PAGE_SIZE, |limit| limit - entries.len());
+        let page = client
+            .get_signatures_for_address_with_config(
+                &address,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until: None,
+                    limit: Some(remaining.min(SIGNATURE_PAGE_SIZE)),
+                    commitment: Some(client.commitment()),
+                },
+            )
+            .await?;
+
+        let page_len = page.len();
+        for status in page {
+            let signature = Signature::from_str(&status.signature).map_err(|source| ClientError::InvalidSignature {
+                signature: status.signature.clone(),
+                source,
+            })?;
+            before = Some(signature);
+            entries.push(fetch_entry(client, program_id, signature).await?);
+        }
+
+        let done = limit.is_some_and(|limit| entries.len() >= limit);
+        if done || page_len < remaining.min(SIGNATURE_PAGE_SIZE) {
+            return Ok(entries);
+        }
+    }
+}
+
+async fn fetch_entry(client: &RpcClient, program_id: Pubkey, signature: Signature) -> Result<HistoryEntry> {
+    let confirmed = client
+        .get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(client.commitment()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await?;
+
+    let meta = confirmed.transaction.meta;
+    let error = meta.as_ref().and_then(|meta| meta.err.clone());
+    let instructions = match confirmed.transaction.transaction.decode() {
+        None => Vec::new(),
+        Some(transaction) => {
+            // Lookup-table addresses follow the static keys, writable first
+            let mut keys = transaction.message.static_account_keys().to_vec();
+            if let Some(OptionSerializer::Some(UiLoadedAddresses { writable, readonly })) =
+                meta.as_ref().map(|meta| &meta.loaded_addresses)
+            {
+                keys.extend(writable.iter().chain(readonly).filter_map(|key| Pubkey::from_str(key).ok()));
+            }
+            let inner_instructions = match meta.as_ref().map(|meta| &meta.inner_instructions) {
+                Some(OptionSerializer::Some(inner_instructions)) => inner_instructions.as_slice(),
+                _ => &[],
+            };
+            decode_instructions(program_id, &keys, transaction.message.instructions(), inner_instructions)
+        }
+    };
+
+    Ok(HistoryEntry {
+        signature,
+        slot: confirmed.slot,
+        block_time: confirmed.block_time,
+        error,
+        instructions,
+    })
+}
+
+/// Decodes the program's top-level instructions, then those it was reached
+/// through by CPI, resolving account indexes against `keys`.
+fn decode_instructions(
+    program_id: Pubkey,
+    keys: &[Pubkey],
+    top_level: &[CompiledInstruction],
+    inner_instructions: &[UiInnerInstructions],
+) -> Vec<DecodedInstruction> {
+    let key = |index: u8| keys.get(index as usize).copied().unwrap_or_default();
+    let mut instructions = Vec::new();
+
+    for (index, instruction) in top_level.iter().enumerate() {
+        if key(instruction.program_id_index) == program_id {
+            let accounts: Vec<Pubkey> = instruction.accounts.iter().map(|&i| key(i)).collect();
+            instructions.push(DecodedInstruction {
+                index: index as u8,
+                inner: false,
+                operation: decode_operation(&instruction.data, &accounts),
+            });
+        }
+    }
+
+    for inner in inner_instructions {
+        for instruction in &inner.instructions {
+            let UiInstruction::Compiled(compiled) = instruction else { continue };
+            if key(compiled.program_id_index) != program_id {
+                continue;
+            }
+            let data = bs58::decode(&compiled.data).into_vec().unwrap_or_default();
+            let accounts: Vec<Pubkey> = compiled.accounts.iter().map(|&i| key(i)).collect();
+            instructions.push(DecodedInstruction {
+                index: inner.index,
+                inner: true,
+                operation: decode_operation(&data, &accounts),
+            });
+        }
+    }
+
+    instructions
+}
+
+/// Decodes instruction data with the program's own `HelloInstruction::unpack`
+/// and names the accounts in the order the processor reads them.
+pub fn decode_operation(data: &[u8], accounts: &[Pubkey]) -> GreetingOperation {
+    let account = |index: usize| accounts.get(index).copied().unwrap_or_default();
+
+    match HelloInstruction::unpack(data) {
+        Ok(HelloInstruction::Initialize { owner }) => GreetingOperation::Initialize {
+            payer: account(0),
+            greeting: account(1),
+            owner,
+        },
+        Ok(HelloInstruction::UpdateMessage { message }) => GreetingOperation::UpdateMessage {
+            owner: account(0),
+            greeting: account(1),
+            message,
+        },
+        Ok(HelloInstruction::IncrementOnly) => GreetingOperation::IncrementOnly {
+            owner: account(0),
+            greeting: account(1),
+        },
+        Ok(HelloInstruction::Close) => GreetingOperation::Close {
+            owner: account(0),
+            greeting: account(1),
+            destination: account(2),
+        },
+        Err(_) => GreetingOperation::Undecodable { data: data.to_vec() },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_transaction_status::UiCompiledInstruction;
+
+    #[test]
+    fn every_instruction_decodes_with_named_accounts() {
+        let [a, b, c, owner] = [(); 4].map(|_| Pubkey::new_unique());
+        let cases = [
+            (HelloInstruction::Initialize { owner }, GreetingOperation::Initialize { payer: a, greeting: b, owner }),
+            (
+                HelloInstruction::UpdateMessage { message: "Hi".to_string() },
+                GreetingOperation::UpdateMessage { owner: a, greeting: b, message: "Hi".to_string() },
+            ),
+            (HelloInstruction::IncrementOnly, GreetingOperation::IncrementOnly { owner: a, greeting: b }),
+            (HelloInstruction::Close, GreetingOperation::Close { owner: a, greeting: b, destination: c }),
+        ];
+        for (instruction, expected) in cases {
+            assert_eq!(decode_operation(&instruction.pack(), &[a, b, c]), expected);
+        }
+    }
+
+    #[test]
+    fn unknown_data_is_kept_undecoded() {
+        for data in [vec![], vec![0xff], vec![1, 0xff, 0xff, 0xff, 0xff]] {
+            assert_eq!(decode_operation(&data, &[]), GreetingOperation::Undecodable { data: data.clone() });
+        }
+    }
+
+    #[test]
+    fn cpi_calls_are_decoded_as_inner_instructions() {
+        let [program_id, caller, owner, greeting] = [(); 4].map(|_| Pubkey::new_unique());
+        let keys = [owner, greeting, caller, program_id];
+        let top_level = [
+            CompiledInstruction::new_from_raw_parts(2, vec![], vec![0, 1, 3]),
+            CompiledInstruction::new_from_raw_parts(3, HelloInstruction::IncrementOnly.pack(), vec![0, 1]),
+        ];
+        let inner = |program_id_index, data: Vec<u8>| {
+            UiInstruction::Compiled(UiCompiledInstruction {
+                program_id_index,
+                accounts: vec![0, 1],
+                data: bs58::encode(data).into_string(),
+                stack_height: Some(2),
+            })
+        };
+        let inner_instructions = [UiInnerInstructions {
+            index: 0,
+            instructions: vec![inner(2, vec![7]), inner(3, HelloInstruction::Close.pack())],
+        }];
+
+        let decoded = decode_instructions(program_id, &keys, &top_level, &inner_instructions);
+        assert_eq!(
+            decoded,
+            [
+                DecodedInstruction { index: 1, inner: false, operation: GreetingOperation::IncrementOnly { owner, greeting } },
+                DecodedInstruction {
+                    index: 0,
+                    inner: true,
+                    operation: GreetingOperation::Close { owner, greeting, destination: Pubkey::default() },
+                },
+            ]
+        );
+    }
+}
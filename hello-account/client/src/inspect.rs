@@ -0,0 +1,285 @@
+use borsh::BorshDeserialize;
+use solana_sdk::{account::Account, pubkey::Pubkey, system_program};
+use std::{fmt, ops::Range};
+
+use hello_account::state::{Greeting, MAX_MESSAGE_LEN};
+
+// Byte offsets of the Borsh-encoded `Greeting` fields
+pub const OWNER_OFFSET: usize = 0;
+pub const COUNT_OFFSET: usize = 32;
+pub const MESSAGE_LEN_OFFSET: usize = 40;
+pub const MESSAGE_OFFSET: usize = 44;
+
+/// A named byte range of the account data. Ranges are clipped to the data, so
+/// a field of a truncated account may be shorter than its encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+    pub range: Range<usize>,
+}
+
+/// Something about an account that explains why it does not decode as a
+/// greeting, or that is otherwise wrong with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    WrongOwner { owner: Pubkey },
+    Executable,
+    Empty,
+    /// Every byte is zero, as `Close` leaves the data
+    Zeroed,
+    Truncated { len: usize, needed: usize },
+    MessageTooLong { len: usize },
+    InvalidUtf8 { valid_up_to: usize },
+    UnexpectedSize { len: usize },
+    /// Leftover bytes after the message, e.g. from a longer previous
+    /// message, which `Greeting::load` rejects
+    NonZeroPadding { offset: usize },
+    NotRentExempt { lamports: u64, minimum: u64 },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongOwner { owner } if *owner == system_program::id() => {
+                write!(f, "Owned by the system program: never initialized, or closed and reassigned")
+            }
+            Self::WrongOwner { owner } => write!(f, "Owned by {}, not the hello-account program", owner),
+            Self::Executable => write!(f, "Account is executable (a program, not a greeting)"),
+            Self::Empty => write!(f, "Account has no data"),
+            Self::Zeroed => write!(f, "All data is zero: the account was closed"),
+            Self::Truncated { len, needed } => write!(f, "Data is {} bytes but the encoding needs {}", len, needed),
+            Self::MessageTooLong { len } => {
+                write!(f, "Message length prefix is {} (max {})", len, MAX_MESSAGE_LEN)
+            }
+            Self::InvalidUtf8 { valid_up_to } => {
+                write!(f, "Message is not valid UTF-8 after byte {}", valid_up_to)
+            }
+            Self::UnexpectedSize { len } => {
+                write!(f, "Data is {} bytes, expected {}", len, Greeting::space())
+            }
+            Self::NonZeroPadding { offset } => {
+                write!(f, "Stale non-zero byte at offset {} after the message", offset)
+            }
+            Self::NotRentExempt { lamports, minimum } => {
+                write!(f, "Balance {} is below the rent-exempt minimum {}", lamports, minimum)
+            }
+        }
+    }
+}
+
+/// A raw account, its layout as a greeting, and what is wrong with it.
+#[derive(Debug)]
+pub struct Inspection {
+    pub address: Pubkey,
+    pub account: Account,
+    /// `Rent::minimum_balance(Greeting::space())` on the cluster
+    pub rent_exempt_minimum: u64,
+    pub fields: Vec<Field>,
+    /// The greeting decoded from the start of the data, ignoring what follows
+    pub greeting: Option<Greeting>,
+    pub problems: Vec<Problem>,
+}
+
+/// Lays out `account` as a greeting of `program_id` and diagnoses it.
+pub fn inspect_account(program_id: Pubkey, address: Pubkey, account: Account, rent_exempt_minimum: u64) -> Inspection {
+    let mut problems = Vec::new();
+    let data = &account.data;
+
+    if account.owner != program_id {
+        problems.push(Problem::WrongOwner { owner: account.owner });
+    }
+    if account.executable {
+        problems.push(Problem::Executable);
+    }
+    if account.lamports < rent_exempt_minimum {
+        problems.push(Problem::NotRentExempt {
+            lamports: account.lamports,
+            minimum: rent_exempt_minimum,
+        });
+    }
+    let zeroed = !data.is_empty() && data.iter().all(|&byte| byte == 0);
+    if data.is_empty() {
+        problems.push(Problem::Empty);
+    } else if zeroed {
+        problems.push(Problem::Zeroed);
+    }
+    if !data.is_empty() && data.len() != Greeting::space() {
+        problems.push(Problem::UnexpectedSize { len: data.len() });
+    }
+
+    let clip = |range: Range<usize>| range.start.min(data.len())..range.end.min(data.len());
+    let mut fields = vec![
+        Field { name: "owner", range: clip(OWNER_OFFSET..COUNT_OFFSET) },
+        Field { name: "count", range: clip(COUNT_OFFSET..MESSAGE_LEN_OFFSET) },
+        Field { name: "message length", range: clip(MESSAGE_LEN_OFFSET..MESSAGE_OFFSET) },
+    ];
+
+    if data.len() < MESSAGE_OFFSET {
+        if !data.is_empty() {
+            problems.push(Problem::Truncated { len: data.len(), needed: MESSAGE_OFFSET });
+        }
+        return Inspection {
+            address,
+            account,
+            rent_exempt_minimum,
+            fields,
+            greeting: None,
+            problems,
+        };
+    }
+
+    let mut len_bytes = [0; 4];
+    len_bytes.copy_from_slice(&data[MESSAGE_LEN_OFFSET..MESSAGE_OFFSET]);
+    let message_len = u32::from_le_bytes(len_bytes) as usize;
+    let message_end = MESSAGE_OFFSET.saturating_add(message_len);
+
+    if message_len > MAX_MESSAGE_LEN {
+        problems.push(Problem::MessageTooLong { len: message_len });
+    }
+    if message_end > data.len() {
+        problems.push(Problem::Truncated { len: data.len(), needed: message_end });
+    } else if let Err(err) = std::str::from_utf8(&data[MESSAGE_OFFSET..message_end]) {
+        problems.push(Problem::InvalidUtf8 { valid_up_to: err.valid_up_to() });
+    }
+
+    fields.push(Field { name: "message", range: clip(MESSAGE_OFFSET..message_end) });
+    if message_end < data.len() {
+        let padding_end = Greeting::space().clamp(message_end, data.len());
+        if padding_end > message_end {
+            fields.push(Field { name: "padding", range: message_end..padding_end });
+        }
+        if padding_end < data.len() {
+            fields.push(Field { name: "unexpected", range: padding_end..data.len() });
+        }
+
+        if let Some(position) = data[message_end..].iter().position(|&byte| byte != 0) {
+            problems.push(Problem::NonZeroPadding { offset: message_end + position });
+        }
+    }
+
+    // A zeroed account decodes, but as a meaningless default greeting
+    let greeting = if zeroed { None } else { Greeting::deserialize(&mut &data[..]).ok() };
+
+    Inspection {
+        address,
+        account,
+        rent_exempt_minimum,
+        fields,
+        greeting,
+        problems,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    const RENT: u64 = 1_000_000;
+
+    fn account(owner: Pubkey, data: Vec<u8>) -> Account {
+        Account { lamports: RENT, data, owner, executable: false, rent_epoch: 0 }
+    }
+
+    /// `greeting` encoded at the start of a `Greeting::space()` buffer.
+    fn greeting_data(greeting: &Greeting) -> Vec<u8> {
+        let mut data = vec![0; Greeting::space()];
+        greeting.serialize(&mut &mut data[..]).unwrap();
+        data
+    }
+
+    fn fields(ranges: &[(&'static str, Range<usize>)]) -> Vec<Field> {
+        ranges.iter().map(|(name, range)| Field { name, range: range.clone() }).collect()
+    }
+
+    #[test]
+    fn problems_and_fields_are_diagnosed() {
+        let program_id = Pubkey::new_unique();
+        let space = Greeting::space();
+        let healthy = Greeting { owner: Pubkey::new_unique(), count: 3, message: "Hello".to_string() };
+        let healthy_data = greeting_data(&healthy);
+        let healthy_fields = fields(&[
+            ("owner", 0..32),
+            ("count", 32..40),
+            ("message length", 40..44),
+            ("message", 44..49),
+            ("padding", 49..space),
+        ]);
+
+        let mut too_long = healthy_data.clone();
+        too_long[MESSAGE_LEN_OFFSET..MESSAGE_OFFSET].copy_from_slice(&(MAX_MESSAGE_LEN as u32 + 1).to_le_bytes());
+        let mut stale = healthy_data.clone();
+        stale[space - 1] = 7;
+
+        let cases = [
+            ("healthy", account(program_id, healthy_data.clone()), vec![], healthy_fields.clone(), Some(healthy.clone())),
+            (
+                "zeroed",
+                account(program_id, vec![0; space]),
+                vec![Problem::Zeroed],
+                fields(&[("owner", 0..32), ("count", 32..40), ("message length", 40..44), ("message", 44..44), ("padding", 44..space)]),
+                None,
+            ),
+            (
+                "truncated header",
+                account(program_id, healthy_data[..20].to_vec()),
+                vec![Problem::UnexpectedSize { len: 20 }, Problem::Truncated { len: 20, needed: MESSAGE_OFFSET }],
+                fields(&[("owner", 0..20), ("count", 20..20), ("message length", 20..20)]),
+                None,
+            ),
+            (
+                "truncated message",
+                account(program_id, healthy_data[..46].to_vec()),
+                vec![Problem::UnexpectedSize { len: 46 }, Problem::Truncated { len: 46, needed: 49 }],
+                fields(&[("owner", 0..32), ("count", 32..40), ("message length", 40..44), ("message", 44..46)]),
+                None,
+            ),
+            (
+                "wrong owner",
+                account(system_program::id(), healthy_data.clone()),
+                vec![Problem::WrongOwner { owner: system_program::id() }],
+                healthy_fields.clone(),
+                Some(healthy.clone()),
+            ),
+            (
+                "length prefix over the maximum",
+                account(program_id, too_long),
+                vec![
+                    Problem::MessageTooLong { len: MAX_MESSAGE_LEN + 1 },
+                    Problem::Truncated { len: space, needed: space + 1 },
+                ],
+                fields(&[("owner", 0..32), ("count", 32..40), ("message length", 40..44), ("message", 44..space)]),
+                None,
+            ),
+            (
+                "stale padding",
+                account(program_id, stale),
+                vec![Problem::NonZeroPadding { offset: space - 1 }],
+                healthy_fields.clone(),
+                Some(healthy.clone()),
+            ),
+        ];
+
+        for (name, account, problems, fields, greeting) in cases {
+            let inspection = inspect_account(program_id, Pubkey::new_unique(), account, RENT);
+            assert_eq!(inspection.problems, problems, "{}", name);
+            assert_eq!(inspection.fields, fields, "{}", name);
+            assert_eq!(inspection.greeting, greeting, "{}", name);
+        }
+    }
+
+    #[test]
+    fn underfunded_and_executable_accounts_are_flagged() {
+        let program_id = Pubkey::new_unique();
+        let mut account = account(program_id, Vec::new());
+        account.lamports = RENT - 1;
+        account.executable = true;
+
+        let inspection = inspect_account(program_id, Pubkey::new_unique(), account, RENT);
+        assert_eq!(
+            inspection.problems,
+            [Problem::Executable, Problem::NotRentExempt { lamports: RENT - 1, minimum: RENT }, Problem::Empty]
+        );
+        assert_eq!(inspection.fields, fields(&[("owner", 0..0), ("count", 0..0), ("message length", 0..0)]));
+    }
+}
@@ -0,0 +1,52 @@
+use hello_account::instruction::HelloInstruction;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+pub fn initialize(program_id: Pubkey, payer: Pubkey, greeting: Pubkey, owner: Pubkey) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(greeting, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: HelloInstruction::Initialize { owner }.pack(),
+    }
+}
+
+pub fn update_message(program_id: Pubkey, owner: Pubkey, greeting: Pubkey, message: &str) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(owner, true),
+            AccountMeta::new(greeting, false),
+        ],
+        data: HelloInstruction::UpdateMessage { message: message.to_string() }.pack(),
+    }
+}
+
+pub fn increment_only(program_id: Pubkey, owner: Pubkey, greeting: Pubkey) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(owner, true),
+            AccountMeta::new(greeting, false),
+        ],
+        data: HelloInstruction::IncrementOnly.pack(),
+    }
+}
+
+pub fn close(program_id: Pubkey, owner: Pubkey, greeting: Pubkey, destination: Pubkey) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(owner, true),
+            AccountMeta::new(greeting, false),
+            AccountMeta::new(destination, false), // destination for lamports
+        ],
+        data: HelloInstruction::Close.pack(),
+    }
+}
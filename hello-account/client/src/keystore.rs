@@ -0,0 +1,317 @@
+use aes_gcm_siv::{
+    aead::{Aead, NewAead, Payload},
+    Aes256GcmSiv, Key, Nonce,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+use zeroize::Zeroizing;
+
+use crate::config::config_dir;
+
+const KEYSTORE_DIR: &str = "keystore";
+const KEYSTORE_VERSION: u32 = 1;
+
+// Argon2id at 64 MiB and 3 passes: about a second on a laptop, per guess
+const KDF_MEMORY_KIB: u32 = 64 * 1024;
+const KDF_ITERATIONS: u32 = 3;
+const KDF_PARALLELISM: u32 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+/// A keypair encrypted under a password, as stored on disk. The pubkey is in
+/// the clear so that entries can be listed without unlocking them.
+#[derive(Debug, Deserialize, Serialize)]
+struct Entry {
+    version: u32,
+    pubkey: String,
+    kdf: Kdf,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Kdf {
+    algorithm: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    salt: String,
+}
+
+// How long an unlocked keypair stays in memory without being used again
+const UNLOCKED_TTL: Duration = Duration::from_secs(15 * 60);
+
+// Keypairs already unlocked by this process, so a shell session or a command
+// using the same entry twice asks for the password once. Each is dropped,
+// which zeroes its secret key, once unused for `UNLOCKED_TTL`.
+fn unlocked() -> &'static Mutex<HashMap<String, (Keypair, Instant)>> {
+    static UNLOCKED: OnceLock<Mutex<HashMap<String, (Keypair, Instant)>>> = OnceLock::new();
+    UNLOCKED.get_or_init(Default::default)
+}
+
+/// The cached keypair of `name`, forgetting every entry that has expired.
+fn cached(name: &str) -> Option<Keypair> {
+    let mut unlocked = unlocked().lock().expect("not poisoned");
+    unlocked.retain(|_, (_, last_used)| last_used.elapsed() < UNLOCKED_TTL);
+    let (keypair, last_used) = unlocked.get_mut(name)?;
+    *last_used = Instant::now();
+    Some(keypair.insecure_clone())
+}
+
+fn keystore_dir() -> PathBuf {
+    config_dir().join(KEYSTORE_DIR)
+}
+
+fn entry_path(name: &str) -> Result<PathBuf> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)) || name.starts_with('.') {
+        bail!("Keystore name `{}` may only contain letters, digits, `-`, `_` and `.`", name);
+    }
+    Ok(keystore_dir().join(format!("{}.json", name)))
+}
+
+impl Kdf {
+    /// Argon2id with the given cost and a fresh random salt.
+    fn argon2id(memory_kib: u32, iterations: u32, parallelism: u32) -> Self {
+        let mut salt = [0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            algorithm: "argon2id".to_string(),
+            memory_kib,
+            iterations,
+            parallelism,
+            salt: BASE64.encode(salt),
+        }
+    }
+
+    fn derive_key(&self, password: &str) -> Result<Zeroizing<[u8; KEY_LEN]>> {
+        if self.algorithm != "argon2id" {
+            bail!("Unsupported key derivation `{}`", self.algorithm);
+        }
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(KEY_LEN))
+            .map_err(|err| anyhow!("Invalid key derivation parameters: {}", err))?;
+        let salt = BASE64.decode(&self.salt)?;
+
+        let mut key = Zeroizing::new([0; KEY_LEN]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), &salt, key.as_mut())
+            .map_err(|err| anyhow!("Key derivation failed: {}", err))?;
+        Ok(key)
+    }
+}
+
+impl Entry {
+    /// Encrypts `keypair` under a key derived from `password` with `kdf`.
+    fn seal(keypair: &Keypair, password: &str, kdf: Kdf) -> Result<Self> {
+        let key = kdf.derive_key(password)?;
+        let mut nonce = [0; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        // The pubkey is authenticated so that an edited entry fails to unlock
+        let pubkey = keypair.pubkey();
+        let secret = Zeroizing::new(keypair.to_bytes());
+        let ciphertext = Aes256GcmSiv::new(&Key::from(*key))
+            .encrypt(&Nonce::from(nonce), Payload { msg: secret.as_ref(), aad: pubkey.as_ref() })
+            .map_err(|_| anyhow!("Encryption failed"))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            pubkey: pubkey.to_string(),
+            kdf,
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        })
+    }
+
+    /// Decrypts the keypair of entry `name`.
+    fn open(&self, name: &str, password: &str) -> Result<Keypair> {
+        let key = self.kdf.derive_key(password)?;
+        let pubkey: Pubkey = self.pubkey.parse()?;
+        let nonce: [u8; NONCE_LEN] = BASE64
+            .decode(&self.nonce)?
+            .try_into()
+            .map_err(|_| anyhow!("Keystore entry `{}` is corrupt", name))?;
+        let ciphertext = BASE64.decode(&self.ciphertext)?;
+        let bytes = Aes256GcmSiv::new(&Key::from(*key))
+            .decrypt(&Nonce::from(nonce), Payload { msg: &ciphertext, aad: pubkey.as_ref() })
+            .map(Zeroizing::new)
+            .map_err(|_| anyhow!("Wrong password for keystore://{}", name))?;
+
+        Keypair::from_bytes(&bytes).map_err(|err| anyhow!("Keystore entry `{}` is corrupt: {}", name, err))
+    }
+}
+
+/// Encrypts `keypair` under `password` and stores it as `name`.
+pub fn store(name: &str, keypair: &Keypair, password: &str) -> Result<PathBuf> {
+    let path = entry_path(name)?;
+    if path.exists() {
+        bail!("Keystore entry `{}` already exists; `keys remove` it first", name);
+    }
+
+    let entry = Entry::seal(keypair, password, Kdf::argon2id(KDF_MEMORY_KIB, KDF_ITERATIONS, KDF_PARALLELISM))?;
+    fs::create_dir_all(keystore_dir())?;
+    fs::write(&path, serde_json::to_string_pretty(&entry)?).with_context(|| format!("Failed to write {}", path.display()))?;
+    restrict_permissions(&path)?;
+    Ok(path)
+}
+
+fn read_entry(name: &str) -> Result<Entry> {
+    read_entry_file(&entry_path(name)?, name)
+}
+
+fn read_entry_file(path: &Path, name: &str) -> Result<Entry> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("No keystore entry `{}` (see `keys list`)", name))?;
+    let entry: Entry = serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))?;
+    if entry.version != KEYSTORE_VERSION {
+        bail!("Keystore entry `{}` has unsupported version {}", name, entry.version);
+    }
+    Ok(entry)
+}
+
+/// Decrypts entry `name`, prompting for its password unless this process
+/// already unlocked it.
+pub fn unlock(name: &str) -> Result<Keypair> {
+    if let Some(keypair) = cached(name) {
+        return Ok(keypair);
+    }
+
+    let entry = read_entry(name)?;
+    let password = Zeroizing::new(rpassword::prompt_password(format!("Password for keystore://{}: ", name))?);
+    let keypair = entry.open(name, &password)?;
+    unlocked().lock().expect("not poisoned").insert(name.to_string(), (keypair.insecure_clone(), Instant::now()));
+    Ok(keypair)
+}
+
+/// Names of every entry with its pubkey, without unlocking them, or why the
+/// entry could not be read.
+pub fn list() -> Result<Vec<(String, Result<String>)>> {
+    list_dir(&keystore_dir())
+}
+
+fn list_dir(dir: &Path) -> Result<Vec<(String, Result<String>)>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    for file in fs::read_dir(dir)? {
+        let path = file?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            let pubkey = read_entry_file(&path, &name).map(|entry| entry.pubkey);
+            entries.push((name, pubkey));
+        }
+    }
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(entries)
+}
+
+pub fn remove(name: &str) -> Result<String> {
+    let entry = read_entry(name)?;
+    fs::remove_file(entry_path(name)?)?;
+    unlocked().lock().expect("not poisoned").remove(name);
+    Ok(entry.pubkey)
+}
+
+/// Asks for a new password twice.
+pub fn prompt_new_password(name: &str) -> Result<Zeroizing<String>> {
+    let password = Zeroizing::new(rpassword::prompt_password(format!("New password for keystore://{}: ", name))?);
+    if password.is_empty() {
+        bail!("Password must not be empty");
+    }
+    if *Zeroizing::new(rpassword::prompt_password("Repeat password: ")?) != *password {
+        bail!("Passwords do not match");
+    }
+    Ok(password)
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The smallest cost Argon2 accepts, so that the tests run in milliseconds
+    fn cheap_kdf() -> Kdf {
+        Kdf::argon2id(8, 1, 1)
+    }
+
+    #[test]
+    fn sealed_keypairs_open_with_their_password() {
+        let keypair = Keypair::new();
+        let entry = Entry::seal(&keypair, "hunter2", cheap_kdf()).unwrap();
+        assert_eq!(entry.pubkey, keypair.pubkey().to_string());
+
+        let opened = entry.open("test", "hunter2").unwrap();
+        assert_eq!(opened.to_bytes(), keypair.to_bytes());
+    }
+
+    #[test]
+    fn wrong_passwords_are_rejected() {
+        let entry = Entry::seal(&Keypair::new(), "hunter2", cheap_kdf()).unwrap();
+        let err = entry.open("test", "hunter3").unwrap_err();
+        assert!(err.to_string().contains("Wrong password"), "{}", err);
+    }
+
+    #[test]
+    fn edited_pubkeys_are_rejected() {
+        let mut entry = Entry::seal(&Keypair::new(), "hunter2", cheap_kdf()).unwrap();
+        entry.pubkey = Keypair::new().pubkey().to_string();
+        assert!(entry.open("test", "hunter2").is_err());
+    }
+
+    #[test]
+    fn names_are_plain_file_stems() {
+        for name in ["main", "dev-1", "cold_wallet", "v1.2"] {
+            assert!(entry_path(name).is_ok(), "{}", name);
+        }
+        for name in ["", ".hidden", "..", "../escape", "a/b", "a\\b", "with space", "ünïcode"] {
+            assert!(entry_path(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn corrupt_entries_do_not_hide_the_others() {
+        let dir = std::env::temp_dir().join(format!("hello-account-keystore-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let keypair = Keypair::new();
+        let entry = Entry::seal(&keypair, "hunter2", cheap_kdf()).unwrap();
+        fs::write(dir.join("good.json"), serde_json::to_string(&entry).unwrap()).unwrap();
+        fs::write(dir.join("bad.json"), "not json").unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let entries = list_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["bad", "good"]);
+        assert!(entries[0].1.is_err());
+        assert_eq!(entries[1].1.as_ref().unwrap(), &keypair.pubkey().to_string());
+    }
+}
@@ -0,0 +1,11 @@
+pub mod client;
+pub mod cluster;
+pub mod error;
+pub mod history;
+pub mod inspect;
+pub mod instructions;
+pub mod transaction;
+
+pub use client::{GreetingClient, InitializeCost, InitializedGreeting, LookupTable};
+pub use cluster::Cluster;
+pub use error::ClientError;
@@ -1,32 +1,84 @@
-use anyhow::Result;
-use borsh::BorshDeserialize;
-use clap::{Parser, Subcommand};
-use solana_client::rpc_client::RpcClient;
+use anyhow::{bail, Result};
+use clap::{Args, Parser, Subcommand};
 use solana_sdk::{
-    commitment_config::CommitmentConfig,
-    instruction::{AccountMeta, Instruction},
+    native_token::{lamports_to_sol, LAMPORTS_PER_SOL},
     pubkey::Pubkey,
     signature::{Keypair, Signer},
-    system_program,
-    transaction::Transaction,
+    slot_hashes::MAX_ENTRIES,
 };
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc, time::Duration};
 
-use hello_account::state::{Greeting, MAX_MESSAGE_LEN};
+use hello_account::state::Greeting;
+use hello_account_client::{
+    history::GreetingOperation,
+    transaction::{ComputeUnitLimit, PriorityFee, SendConfig},
+    Cluster, GreetingClient,
+};
+
+mod address_book;
+mod batch;
+mod bench;
+mod config;
+mod deploy;
+mod keystore;
+mod shell;
+mod signer;
+mod snapshot;
 
-// Replace this with your deployed program ID
+use address_book::AddressBook;
+use batch::{BatchOptions, LookupTableChoice};
+use bench::BenchOptions;
+use config::ClientConfig;
+use deploy::{AuthorityChange, DeployOptions, UpgradeOptions};
+use signer::SignerSource;
+
+// Used until `deploy` records a program id in the client config
 const PROGRAM_ID: &str = "9SnsoxmcQRYt2a7jbL9Vb84m2HJLjCfV8WwV7c2Joudh";
 
+// Airdrop at least this much on localnet so that later commands are covered too
+const LOCALNET_AIRDROP_LAMPORTS: u64 = LAMPORTS_PER_SOL;
+
+// A deactivated lookup table can be closed once it leaves SlotHashes
+const LOOKUP_TABLE_COOL_DOWN_SLOTS: usize = MAX_ENTRIES;
+
+// Bytes per line of the `inspect` hex dump
+const HEX_ROW_LEN: usize = 16;
+
 #[derive(Parser)]
 #[command(name = "hello-account-client")]
 #[command(about = "CLI client for Hello Account program", long_about = None)]
 struct Cli {
-    #[arg(short, long, default_value = "http://localhost:8899")]
-    rpc_url: String,
+    #[arg(
+        short = 'u',
+        long = "url",
+        visible_alias = "rpc-url",
+        short_alias = 'r',
+        default_value = "localnet",
+        help = "Cluster: localnet, devnet, testnet, mainnet-beta, or an RPC URL"
+    )]
+    cluster: Cluster,
 
-    #[arg(short, long, default_value = "~/.config/solana/id.json")]
+    #[arg(short, long, default_value = "~/.config/solana/id.json", help = "Default signer for both fee payer and owner")]
     keypair_path: String,
 
+    #[arg(long, help = "Fee payer signer: keypair path, file://, keystore://, prompt:// or stdin:// (defaults to --keypair-path)")]
+    fee_payer: Option<String>,
+
+    #[arg(long, help = "Greeting owner signer: keypair path, file://, keystore://, prompt:// or stdin:// (defaults to the fee payer)")]
+    owner_keypair: Option<String>,
+
+    #[arg(long, help = "Compute unit price in micro-lamports, or `auto` to use recent prioritization fees")]
+    priority_fee: Option<PriorityFee>,
+
+    #[arg(long, help = "Compute unit limit, or `auto` to size it from a simulation (default: auto when a priority fee is set)")]
+    compute_unit_limit: Option<ComputeUnitLimit>,
+
+    #[arg(long, default_value_t = 90, help = "Seconds to keep resending before giving up on confirmation")]
+    confirm_timeout: u64,
+
+    #[arg(long, help = "Program id (defaults to the one recorded by `deploy`, then the built-in one)")]
+    program_id: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -35,13 +87,19 @@ struct Cli {
 enum Commands {
     /// Initialize a new greeting account
     Init {
-        #[arg(short, long, help = "Owner pubkey (defaults to payer)")]
+        #[arg(short, long, help = "Owner pubkey (defaults to the owner signer)")]
         owner: Option<String>,
+
+        #[arg(long, help = "Save the new account in the address book under this alias")]
+        alias: Option<String>,
+
+        #[arg(long, requires = "alias", help = "Also keep the greeting account's keypair in the address book")]
+        save_keypair: bool,
     },
     
     /// Update the greeting message
     Update {
-        #[arg(short, long, help = "Greeting account address")]
+        #[arg(short, long, help = "Greeting account address or alias")]
         account: String,
         
         #[arg(short, long, help = "New message")]
@@ -50,155 +108,722 @@ enum Commands {
     
     /// Increment the counter only
     Increment {
-        #[arg(short, long, help = "Greeting account address")]
+        #[arg(short, long, help = "Greeting account address or alias")]
         account: String,
     },
     
     /// Close the greeting account
     Close {
-        #[arg(short, long, help = "Greeting account address")]
+        #[arg(short, long, help = "Greeting account address or alias")]
         account: String,
     },
     
     /// Get greeting account data
     Get {
-        #[arg(short, long, help = "Greeting account address")]
+        #[arg(short, long, help = "Greeting account address or alias")]
+        account: String,
+    },
+
+    /// Dump a raw account annotated with the greeting layout and diagnose it
+    Inspect {
+        #[arg(short, long, help = "Greeting account address or alias")]
+        account: String,
+    },
+
+    /// Show the transactions that touched a greeting account, newest first
+    History {
+        #[arg(short, long, help = "Greeting account address or alias")]
         account: String,
+
+        #[arg(short, long, default_value_t = 20, help = "Maximum number of transactions to show")]
+        limit: usize,
+    },
+
+    /// Apply init/update/increment/close operations listed in a manifest
+    Batch {
+        #[arg(short, long, help = "Manifest file (.csv, .json, .yaml or .yml)")]
+        manifest: String,
+
+        #[arg(short, long, help = "Report file; rows that already succeeded in it are skipped (defaults to <manifest>.report.json)")]
+        report: Option<String>,
+
+        #[arg(short, long, default_value_t = 8, help = "Maximum number of transactions in flight")]
+        concurrency: usize,
+
+        #[arg(long, help = "Address lookup table holding the manifest's accounts (by default one is created when it helps)")]
+        lookup_table: Option<String>,
+
+        #[arg(long, conflicts_with = "lookup_table", help = "Send legacy transactions without a lookup table")]
+        no_lookup_table: bool,
+    },
+
+    /// Load-test the program with concurrent updates and increments
+    Bench {
+        #[arg(long, default_value_t = 10, help = "Number of greeting accounts to provision")]
+        accounts: usize,
+
+        #[arg(short, long, default_value_t = 1000, help = "Number of transactions to send")]
+        transactions: usize,
+
+        #[arg(long, default_value_t = 50.0, value_parser = bench::parse_rate, help = "Target transactions per second")]
+        rate: f64,
+
+        #[arg(short, long, default_value_t = 64, help = "Maximum number of transactions in flight")]
+        concurrency: usize,
+
+        #[arg(long, default_value_t = 0.5, help = "Share of IncrementOnly transactions; the rest are UpdateMessage")]
+        increment_ratio: f64,
+
+        #[arg(long, help = "Allow running against a cluster other than a local validator")]
+        allow_remote: bool,
+    },
+
+    /// Write greetings to a JSON snapshot
+    Export {
+        #[arg(short, long = "account", help = "Greeting account address or alias; repeat for several")]
+        accounts: Vec<String>,
+
+        #[arg(long, conflicts_with = "accounts", help = "Export every greeting of the program")]
+        all: bool,
+
+        #[arg(short, long, help = "Snapshot file to write")]
+        output: String,
+    },
+
+    /// Re-create the greetings of a snapshot on this cluster
+    Import {
+        #[arg(short, long, help = "Snapshot file written by `export`")]
+        snapshot: String,
+
+        #[arg(long, help = "Instead of sending transactions, write `solana-test-validator --account` files to this directory")]
+        fixtures: Option<String>,
+    },
+
+    /// Deploy the program through the upgradeable loader and use it from now on
+    Deploy {
+        #[arg(short, long, help = "Compiled program, e.g. target/deploy/hello_account.so")]
+        program: String,
+
+        #[arg(long, help = "Program keypair (defaults to <name>-keypair.json next to the program, created if missing)")]
+        program_keypair: Option<String>,
+
+        #[arg(long, help = "Maximum program size to allow for upgrades (defaults to twice the current size)")]
+        max_len: Option<usize>,
+
+        #[command(flatten)]
+        authority: AuthorityArgs,
+
+        #[arg(short, long, default_value_t = 8, help = "Maximum number of buffer writes in flight")]
+        concurrency: usize,
+    },
+
+    /// Replace the program's bytes with a new build
+    Upgrade {
+        #[arg(short, long, help = "Compiled program, e.g. target/deploy/hello_account.so")]
+        program: String,
+
+        #[command(flatten)]
+        authority: AuthorityArgs,
+
+        #[arg(short, long, default_value_t = 8, help = "Maximum number of buffer writes in flight")]
+        concurrency: usize,
+    },
+
+    /// Check that the deployed program matches a local build
+    Verify {
+        #[arg(short, long, help = "Compiled program, e.g. target/deploy/hello_account.so")]
+        program: String,
+    },
+
+    /// Transfer the program's upgrade authority, or make it immutable
+    SetUpgradeAuthority {
+        #[command(flatten)]
+        authority: AuthorityArgs,
+    },
+
+    /// Manage greeting account aliases
+    #[command(subcommand)]
+    Accounts(AccountsCommand),
+
+    /// Manage the encrypted keystore used by `keystore://` signers
+    #[command(subcommand)]
+    Keys(KeysCommand),
+
+    /// Manage address lookup tables, with the fee payer as authority
+    #[command(subcommand)]
+    Alt(AltCommand),
+
+    /// Start an interactive session that keeps the connection and signers loaded
+    Shell,
+}
+
+#[derive(Subcommand)]
+enum AltCommand {
+    /// Create a lookup table, optionally filled with addresses
+    Create {
+        #[arg(short, long = "address", help = "Address or alias to add; repeat for several")]
+        addresses: Vec<String>,
+    },
+
+    /// Add addresses to a lookup table
+    Extend {
+        #[arg(short, long, help = "Lookup table address")]
+        table: String,
+
+        #[arg(short, long = "address", required = true, help = "Address or alias to add; repeat for several")]
+        addresses: Vec<String>,
+    },
+
+    /// Show a lookup table's addresses and state
+    Show {
+        #[arg(short, long, help = "Lookup table address")]
+        table: String,
+    },
+
+    /// Deactivate a lookup table, or close it once its cool-down has passed
+    Close {
+        #[arg(short, long, help = "Lookup table address")]
+        table: String,
+    },
+}
+
+#[derive(Args)]
+struct AuthorityArgs {
+    #[arg(long, help = "Current upgrade authority signer (defaults to the fee payer)")]
+    upgrade_authority: Option<String>,
+
+    #[arg(long, help = "Transfer the upgrade authority to this pubkey afterwards")]
+    new_upgrade_authority: Option<String>,
+
+    #[arg(long = "final", conflicts_with = "new_upgrade_authority", help = "Make the program immutable afterwards")]
+    make_final: bool,
+}
+
+impl AuthorityArgs {
+    fn load(&self, payer: &Keypair) -> Result<(Keypair, AuthorityChange)> {
+        let authority = match &self.upgrade_authority {
+            Some(uri) => SignerSource::parse(uri)?.load("upgrade authority")?,
+            None => payer.insecure_clone(),
+        };
+        let change = match (&self.new_upgrade_authority, self.make_final) {
+            (Some(new_authority), _) => AuthorityChange::Transfer(Pubkey::from_str(new_authority)?),
+            (None, true) => AuthorityChange::Final,
+            (None, false) => AuthorityChange::Keep,
+        };
+        Ok((authority, change))
+    }
+}
+
+#[derive(Subcommand)]
+enum AccountsCommand {
+    /// List saved aliases
+    List,
+
+    /// Save an existing greeting account under an alias
+    Add {
+        alias: String,
+        address: String,
+    },
+
+    /// Rename an alias
+    Rename {
+        alias: String,
+        new_alias: String,
+    },
+
+    /// Remove an alias from the address book
+    Forget {
+        alias: String,
+
+        #[arg(long, help = "Also delete the saved keypair file, if any")]
+        delete_keypair: bool,
     },
 }
 
-fn main() -> Result<()> {
+#[derive(Subcommand)]
+enum KeysCommand {
+    /// Generate a new keypair into the keystore
+    Generate {
+        name: String,
+    },
+
+    /// Encrypt an existing keypair into the keystore
+    Import {
+        name: String,
+
+        #[arg(long, help = "Signer to import: keypair path, file://, prompt:// or stdin://")]
+        from: String,
+    },
+
+    /// List keystore entries and their pubkeys
+    List,
+
+    /// Delete a keystore entry
+    Remove {
+        name: String,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
     let cli = Cli::parse();
     
-    // Setup RPC client
-    let rpc_client = RpcClient::new_with_commitment(
-        cli.rpc_url.clone(),
-        CommitmentConfig::confirmed(),
-    );
+    // The keystore needs no connection or signers, and may hold the signers
+    if let Commands::Keys(command) = &cli.command {
+        return manage_keys(command);
+    }
     
-    // Load payer keypair
-    let keypair_path = shellexpand::tilde(&cli.keypair_path).to_string();
-    let payer = solana_sdk::signature::read_keypair_file(&keypair_path)
-        .expect("Failed to read keypair file");
+    // Load fee payer and owner signers; the owner falls back to the payer
+    let payer_uri = cli.fee_payer.as_deref().unwrap_or(&cli.keypair_path);
+    let payer = SignerSource::parse(payer_uri)?.load("fee payer")?;
+    let owner = match cli.owner_keypair.as_deref() {
+        Some(owner_uri) if owner_uri != payer_uri => SignerSource::parse(owner_uri)?.load("owner")?,
+        _ => payer.insecure_clone(),
+    };
     
     println!("Using payer: {}", payer.pubkey());
-    println!("RPC URL: {}", cli.rpc_url);
+    if owner.pubkey() != payer.pubkey() {
+        println!("Using owner: {}", owner.pubkey());
+    }
+    println!("RPC URL: {}", cli.cluster.rpc_url());
+    println!("WebSocket URL: {}", cli.cluster.ws_url());
+    
+    let send_config = SendConfig {
+        priority_fee: cli.priority_fee,
+        compute_unit_limit: cli.compute_unit_limit,
+        timeout: Duration::from_secs(cli.confirm_timeout),
+        progress: Some(Arc::new(|progress| println!("{}", progress))),
+        ..SendConfig::default()
+    };
+    
+    let program_id = match cli.program_id {
+        Some(program_id) => program_id,
+        None => ClientConfig::load()?
+            .cluster(&cli.cluster)
+            .and_then(|cluster_config| cluster_config.program_id.clone())
+            .unwrap_or_else(|| PROGRAM_ID.to_string()),
+    };
+    println!("Program ID: {}", program_id);
+    
+    // Setup RPC client
+    let client = GreetingClient::connect(cli.cluster.rpc_url(), Pubkey::from_str(&program_id)?)
+        .with_send_config(send_config);
+    
+    let ctx = Context { cluster: cli.cluster, client, payer, owner };
     
     match cli.command {
-        Commands::Init { owner } => {
-            let owner_pubkey = if let Some(owner_str) = owner {
+        Commands::Shell => shell::run(&ctx).await?,
+        command => {
+            execute(&ctx, command).await?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Connection and signers shared by every command, so that the shell can run
+/// many commands without reconnecting or reloading keys.
+struct Context {
+    cluster: Cluster,
+    client: GreetingClient,
+    payer: Keypair,
+    owner: Keypair,
+}
+
+/// Runs one command and returns the greeting account it operated on, if any.
+async fn execute(ctx: &Context, command: Commands) -> Result<Option<Pubkey>> {
+    let Context { cluster, client, payer, owner } = ctx;
+    
+    match command {
+        Commands::Init { owner: owner_arg, alias, save_keypair } => {
+            let owner_pubkey = if let Some(owner_str) = owner_arg {
                 Pubkey::from_str(&owner_str)?
             } else {
-                payer.pubkey()
+                owner.pubkey()
             };
             
-            initialize_greeting(&rpc_client, &payer, owner_pubkey)?;
+            // Reject a taken alias before paying for the account
+            let mut book = AddressBook::load()?;
+            if let Some(alias) = &alias {
+                book.check_available(alias)?;
+            }
+            
+            ensure_funded(cluster, client, payer, owner_pubkey).await?;
+            
+            let greeting_keypair = Keypair::new();
+            let greeting = initialize_greeting(client, payer, &greeting_keypair, owner_pubkey).await?;
+            
+            if let Some(alias) = alias {
+                if save_keypair {
+                    let path = book.add_with_keypair(&alias, &greeting_keypair)?;
+                    println!("Saved as `{}` (keypair: {})", alias, path.display());
+                } else {
+                    book.add(&alias, greeting)?;
+                    println!("Saved as `{}`", alias);
+                }
+                book.save()?;
+            }
+            Ok(Some(greeting))
         }
         
         Commands::Update { account, message } => {
-            let account_pubkey = Pubkey::from_str(&account)?;
-            update_message(&rpc_client, &payer, account_pubkey, message)?;
+            let account_pubkey = AddressBook::load()?.resolve(&account)?;
+            update_message(client, payer, owner, account_pubkey, message).await?;
+            Ok(Some(account_pubkey))
         }
         
         Commands::Increment { account } => {
-            let account_pubkey = Pubkey::from_str(&account)?;
-            increment_only(&rpc_client, &payer, account_pubkey)?;
+            let account_pubkey = AddressBook::load()?.resolve(&account)?;
+            increment_only(client, payer, owner, account_pubkey).await?;
+            Ok(Some(account_pubkey))
         }
         
         Commands::Close { account } => {
-            let account_pubkey = Pubkey::from_str(&account)?;
-            close_greeting(&rpc_client, &payer, account_pubkey)?;
+            let account_pubkey = AddressBook::load()?.resolve(&account)?;
+            close_greeting(client, payer, owner, account_pubkey).await?;
+            Ok(Some(account_pubkey))
         }
         
         Commands::Get { account } => {
-            let account_pubkey = Pubkey::from_str(&account)?;
-            get_greeting(&rpc_client, account_pubkey)?;
+            let account_pubkey = AddressBook::load()?.resolve(&account)?;
+            get_greeting(client, account_pubkey).await?;
+            Ok(Some(account_pubkey))
+        }
+        
+        Commands::Inspect { account } => {
+            let account_pubkey = AddressBook::load()?.resolve(&account)?;
+            inspect_account(client, account_pubkey).await?;
+            Ok(Some(account_pubkey))
+        }
+        
+        Commands::History { account, limit } => {
+            let account_pubkey = AddressBook::load()?.resolve(&account)?;
+            show_history(client, account_pubkey, limit).await?;
+            Ok(Some(account_pubkey))
+        }
+        
+        Commands::Batch { manifest, report, concurrency, lookup_table, no_lookup_table } => {
+            let lookup_table = match lookup_table {
+                Some(table) => LookupTableChoice::Existing(Pubkey::from_str(&table)?),
+                None if no_lookup_table => LookupTableChoice::None,
+                None => LookupTableChoice::Auto,
+            };
+            batch::run(
+                client,
+                payer,
+                owner,
+                BatchOptions {
+                    manifest_path: &manifest,
+                    report_path: report.as_deref(),
+                    concurrency,
+                    lookup_table,
+                },
+            )
+            .await?;
+            Ok(None)
+        }
+        
+        Commands::Bench { accounts, transactions, rate, concurrency, increment_ratio, allow_remote } => {
+            if !cluster.is_local() && !allow_remote {
+                bail!("Refusing to benchmark {} without --allow-remote", cluster);
+            }
+            bench::run(
+                client,
+                payer,
+                owner,
+                BenchOptions {
+                    accounts,
+                    transactions,
+                    rate,
+                    concurrency,
+                    increment_ratio,
+                },
+            )
+            .await?;
+            Ok(None)
+        }
+        
+        Commands::Export { accounts, all, output } => {
+            if accounts.is_empty() && !all {
+                bail!("Pass --account at least once, or --all");
+            }
+            let book = AddressBook::load()?;
+            let accounts = accounts
+                .iter()
+                .map(|account| book.resolve(account))
+                .collect::<Result<Vec<_>>>()?;
+            snapshot::export(client, &accounts, &output).await?;
+            Ok(None)
+        }
+        
+        Commands::Import { snapshot: snapshot_path, fixtures } => {
+            match fixtures {
+                Some(dir) => snapshot::write_fixtures(client.program_id(), &snapshot_path, &dir)?,
+                None => snapshot::replay(client, payer, owner, &snapshot_path).await?,
+            }
+            Ok(None)
+        }
+        
+        Commands::Deploy { program, program_keypair, max_len, authority, concurrency } => {
+            let (authority, authority_change) = authority.load(payer)?;
+            deploy::deploy(
+                client,
+                payer,
+                DeployOptions {
+                    cluster,
+                    program_path: &program,
+                    program_keypair_path: program_keypair.as_deref(),
+                    max_len,
+                    authority: &authority,
+                    authority_change,
+                    concurrency,
+                },
+            )
+            .await?;
+            Ok(None)
+        }
+        
+        Commands::Upgrade { program, authority, concurrency } => {
+            let (authority, authority_change) = authority.load(payer)?;
+            deploy::upgrade(
+                client,
+                payer,
+                UpgradeOptions {
+                    cluster,
+                    program_path: &program,
+                    program_id: client.program_id(),
+                    authority: &authority,
+                    authority_change,
+                    concurrency,
+                },
+            )
+            .await?;
+            Ok(None)
+        }
+        
+        Commands::Verify { program } => {
+            deploy::verify(client, client.program_id(), &program).await?;
+            Ok(None)
+        }
+        
+        Commands::SetUpgradeAuthority { authority } => {
+            let (authority, authority_change) = authority.load(payer)?;
+            if matches!(authority_change, AuthorityChange::Keep) {
+                bail!("Pass --new-upgrade-authority or --final");
+            }
+            deploy::change_authority(client, payer, client.program_id(), &authority, authority_change).await?;
+            Ok(None)
+        }
+        
+        Commands::Alt(command) => {
+            manage_lookup_tables(client, payer, command).await?;
+            Ok(None)
+        }
+        
+        Commands::Accounts(command) => {
+            manage_accounts(command)?;
+            Ok(None)
+        }
+        
+        Commands::Keys(command) => {
+            manage_keys(&command)?;
+            Ok(None)
+        }
+        
+        Commands::Shell => bail!("Already in a shell"),
+    }
+}
+
+async fn manage_lookup_tables(client: &GreetingClient, payer: &Keypair, command: AltCommand) -> Result<()> {
+    let book = AddressBook::load()?;
+    let resolve = |addresses: &[String]| addresses.iter().map(|address| book.resolve(address)).collect::<Result<Vec<_>>>();
+    
+    match command {
+        AltCommand::Create { addresses } => {
+            let addresses = resolve(&addresses)?;
+            let (table, signature) = client.create_lookup_table(payer, payer).await?;
+            println!("\nCreated lookup table {}", table);
+            println!("Signature: {}", signature);
+            if !addresses.is_empty() {
+                client.extend_lookup_table(payer, payer, table, &addresses).await?;
+                println!("Added {} address(es)", addresses.len());
+            }
+        }
+        AltCommand::Extend { table, addresses } => {
+            let table = Pubkey::from_str(&table)?;
+            let addresses = resolve(&addresses)?;
+            for signature in client.extend_lookup_table(payer, payer, table, &addresses).await? {
+                println!("Signature: {}", signature);
+            }
+            println!("\nAdded {} address(es) to {}", addresses.len(), table);
+        }
+        AltCommand::Show { table } => {
+            let table = client.get_lookup_table(Pubkey::from_str(&table)?).await?;
+            println!("\nLookup table {}", table.account.key);
+            match table.meta.authority {
+                Some(authority) => println!("Authority: {}", authority),
+                None => println!("Authority: none (frozen)"),
+            }
+            if table.is_deactivated() {
+                println!("Deactivated at slot {}", table.meta.deactivation_slot);
+            }
+            println!("Last extended at slot {}", table.meta.last_extended_slot);
+            for (index, address) in table.account.addresses.iter().enumerate() {
+                println!("  {:>3}  {}", index, address);
+            }
+        }
+        AltCommand::Close { table } => {
+            let table = client.get_lookup_table(Pubkey::from_str(&table)?).await?;
+            let key = table.account.key;
+            if table.is_deactivated() {
+                let signature = client.close_lookup_table(payer, payer, key, payer.pubkey()).await?;
+                println!("\nClosed lookup table {}", key);
+                println!("Signature: {}", signature);
+            } else {
+                let signature = client.deactivate_lookup_table(payer, payer, key).await?;
+                println!("\nDeactivated lookup table {}", key);
+                println!("Signature: {}", signature);
+                println!("Run `alt close` again in about {} slots to close it and reclaim its rent", LOOKUP_TABLE_COOL_DOWN_SLOTS);
+            }
         }
     }
     
     Ok(())
 }
 
-fn initialize_greeting(
-    client: &RpcClient,
-    payer: &Keypair,
-    owner: Pubkey,
-) -> Result<()> {
-    println!("\nInitializing greeting account...");
+fn manage_accounts(command: AccountsCommand) -> Result<()> {
+    let mut book = AddressBook::load()?;
+    
+    match command {
+        AccountsCommand::List => {
+            let mut entries = book.entries().peekable();
+            if entries.peek().is_none() {
+                println!("No saved accounts");
+            }
+            for (alias, entry) in entries {
+                match &entry.keypair {
+                    Some(path) => println!("{:<20} {}  (keypair: {})", alias, entry.address, path.display()),
+                    None => println!("{:<20} {}", alias, entry.address),
+                }
+            }
+            return Ok(());
+        }
+        AccountsCommand::Add { alias, address } => {
+            book.add(&alias, Pubkey::from_str(&address)?)?;
+            println!("Saved {} as `{}`", address, alias);
+        }
+        AccountsCommand::Rename { alias, new_alias } => {
+            book.rename(&alias, &new_alias)?;
+            println!("Renamed `{}` to `{}`", alias, new_alias);
+        }
+        AccountsCommand::Forget { alias, delete_keypair } => {
+            let entry = book.forget(&alias, delete_keypair)?;
+            println!("Forgot `{}` ({})", alias, entry.address);
+            if let (false, Some(path)) = (delete_keypair, &entry.keypair) {
+                println!("Keypair kept at {}", path.display());
+            }
+        }
+    }
     
-    let program_id = Pubkey::from_str(PROGRAM_ID)?;
-    let greeting_keypair = Keypair::new();
+    book.save()
+}
+
+fn manage_keys(command: &KeysCommand) -> Result<()> {
+    match command {
+        KeysCommand::Generate { name } => {
+            let password = keystore::prompt_new_password(name)?;
+            let keypair = Keypair::new();
+            let path = keystore::store(name, &keypair, &password)?;
+            println!("Generated {} as keystore://{}", keypair.pubkey(), name);
+            println!("Saved to {}", path.display());
+        }
+        KeysCommand::Import { name, from } => {
+            let keypair = SignerSource::parse(from)?.load("imported")?;
+            let password = keystore::prompt_new_password(name)?;
+            let path = keystore::store(name, &keypair, &password)?;
+            println!("Imported {} as keystore://{}", keypair.pubkey(), name);
+            println!("Saved to {}", path.display());
+        }
+        KeysCommand::List => {
+            let entries = keystore::list()?;
+            if entries.is_empty() {
+                println!("No keystore entries");
+            }
+            for (name, pubkey) in entries {
+                match pubkey {
+                    Ok(pubkey) => println!("{:<20} {}", name, pubkey),
+                    Err(err) => println!("{:<20} unreadable: {:#}", name, err),
+                }
+            }
+        }
+        KeysCommand::Remove { name } => {
+            let pubkey = keystore::remove(name)?;
+            println!("Removed keystore://{} ({})", name, pubkey);
+        }
+    }
     
-    println!("New greeting account: {}", greeting_keypair.pubkey());
-    println!("Owner: {}", owner);
+    Ok(())
+}
+
+/// Shows what `init` will cost and, on a local validator, airdrops the
+/// shortfall so that a fresh key can initialize right away.
+async fn ensure_funded(cluster: &Cluster, client: &GreetingClient, payer: &Keypair, owner: Pubkey) -> Result<()> {
+    let cost = client.initialize_cost(payer.pubkey(), owner).await?;
+    
+    println!("\nCost breakdown:");
+    println!("  Rent ({} bytes):  {}", Greeting::space(), format_sol(cost.rent));
+    println!("  Transaction fee:  {}", format_sol(cost.fee));
+    println!("  Total:            {}", format_sol(cost.total()));
+    println!("  Payer balance:    {}", format_sol(cost.balance));
+    
+    let shortfall = cost.shortfall();
+    if shortfall == 0 {
+        return Ok(());
+    }
+    if !cluster.is_local() {
+        bail!("Payer {} is short {} to initialize a greeting; fund it first", payer.pubkey(), format_sol(shortfall));
+    }
     
-    // Build instruction data: [variant(1 byte), owner(32 bytes)]
-    let mut instruction_data = vec![0u8]; // variant 0 = Initialize
-    instruction_data.extend_from_slice(&owner.to_bytes());
-    
-    let instruction = Instruction {
-        program_id,
-        accounts: vec![
-            AccountMeta::new(payer.pubkey(), true),
-            AccountMeta::new(greeting_keypair.pubkey(), true),
-            AccountMeta::new_readonly(system_program::id(), false),
-        ],
-        data: instruction_data,
-    };
+    let lamports = shortfall.max(LOCALNET_AIRDROP_LAMPORTS);
+    println!("\nRequesting airdrop of {}...", format_sol(lamports));
+    let signature = client.airdrop(payer.pubkey(), lamports).await?;
+    println!("Airdrop signature: {}", signature);
     
-    let recent_blockhash = client.get_latest_blockhash()?;
-    let transaction = Transaction::new_signed_with_payer(
-        &[instruction],
-        Some(&payer.pubkey()),
-        &[payer, &greeting_keypair],
-        recent_blockhash,
-    );
+    Ok(())
+}
+
+fn format_sol(lamports: u64) -> String {
+    format!("{} SOL ({} lamports)", lamports_to_sol(lamports), lamports)
+}
+
+async fn initialize_greeting(
+    client: &GreetingClient,
+    payer: &Keypair,
+    greeting_keypair: &Keypair,
+    owner: Pubkey,
+) -> Result<Pubkey> {
+    println!("\nInitializing greeting account...");
+    println!("Owner: {}", owner);
     
-    let signature = client.send_and_confirm_transaction(&transaction)?;
+    let signature = client.initialize_with_keypair(payer, greeting_keypair, owner).await?;
     
     println!("Success!");
     println!("Signature: {}", signature);
     println!("Greeting Account: {}", greeting_keypair.pubkey());
     
-    Ok(())
+    Ok(greeting_keypair.pubkey())
 }
 
-fn update_message(
-    client: &RpcClient,
+async fn update_message(
+    client: &GreetingClient,
     payer: &Keypair,
+    owner: &Keypair,
     greeting_account: Pubkey,
     message: String,
 ) -> Result<()> {
     println!("\nUpdating message...");
     
-    if message.len() > MAX_MESSAGE_LEN {
-        anyhow::bail!("Message too long! Max length: {}", MAX_MESSAGE_LEN);
-    }
-    
-    let program_id = Pubkey::from_str(PROGRAM_ID)?;
-    
-    // Build instruction data: [variant(1 byte), message(variable)]
-    let mut instruction_data = vec![1u8]; // variant 1 = UpdateMessage
-    instruction_data.extend_from_slice(message.as_bytes());
-    
-    let instruction = Instruction {
-        program_id,
-        accounts: vec![
-            AccountMeta::new(payer.pubkey(), true),
-            AccountMeta::new(greeting_account, false),
-        ],
-        data: instruction_data,
-    };
-    
-    let recent_blockhash = client.get_latest_blockhash()?;
-    let transaction = Transaction::new_signed_with_payer(
-        &[instruction],
-        Some(&payer.pubkey()),
-        &[payer],
-        recent_blockhash,
-    );
-    
-    let signature = client.send_and_confirm_transaction(&transaction)?;
+    let signature = client.update_message(payer, owner, greeting_account, &message).await?;
     
     println!("Success!");
     println!("Signature: {}", signature);
@@ -206,36 +831,15 @@ fn update_message(
     Ok(())
 }
 
-fn increment_only(
-    client: &RpcClient,
+async fn increment_only(
+    client: &GreetingClient,
     payer: &Keypair,
+    owner: &Keypair,
     greeting_account: Pubkey,
 ) -> Result<()> {
     println!("\nIncrementing counter...");
     
-    let program_id = Pubkey::from_str(PROGRAM_ID)?;
-    
-    // Build instruction data: [variant(1 byte)]
-    let instruction_data = vec![2u8]; // variant 2 = IncrementOnly
-    
-    let instruction = Instruction {
-        program_id,
-        accounts: vec![
-            AccountMeta::new(payer.pubkey(), true),
-            AccountMeta::new(greeting_account, false),
-        ],
-        data: instruction_data,
-    };
-    
-    let recent_blockhash = client.get_latest_blockhash()?;
-    let transaction = Transaction::new_signed_with_payer(
-        &[instruction],
-        Some(&payer.pubkey()),
-        &[payer],
-        recent_blockhash,
-    );
-    
-    let signature = client.send_and_confirm_transaction(&transaction)?;
+    let signature = client.increment(payer, owner, greeting_account).await?;
     
     println!("Success!");
     println!("Signature: {}", signature);
@@ -243,37 +847,15 @@ fn increment_only(
     Ok(())
 }
 
-fn close_greeting(
-    client: &RpcClient,
+async fn close_greeting(
+    client: &GreetingClient,
     payer: &Keypair,
+    owner: &Keypair,
     greeting_account: Pubkey,
 ) -> Result<()> {
     println!("\nClosing greeting account...");
     
-    let program_id = Pubkey::from_str(PROGRAM_ID)?;
-    
-    // Build instruction data: [variant(1 byte)]
-    let instruction_data = vec![3u8]; // variant 3 = Close
-    
-    let instruction = Instruction {
-        program_id,
-        accounts: vec![
-            AccountMeta::new(payer.pubkey(), true),
-            AccountMeta::new(greeting_account, false),
-            AccountMeta::new(payer.pubkey(), false), // destination for lamports
-        ],
-        data: instruction_data,
-    };
-    
-    let recent_blockhash = client.get_latest_blockhash()?;
-    let transaction = Transaction::new_signed_with_payer(
-        &[instruction],
-        Some(&payer.pubkey()),
-        &[payer],
-        recent_blockhash,
-    );
-    
-    let signature = client.send_and_confirm_transaction(&transaction)?;
+    let signature = client.close(payer, owner, greeting_account, payer.pubkey()).await?;
     
     println!("Success!");
     println!("Signature: {}", signature);
@@ -282,16 +864,128 @@ fn close_greeting(
     Ok(())
 }
 
-fn get_greeting(client: &RpcClient, greeting_account: Pubkey) -> Result<()> {
+async fn get_greeting(client: &GreetingClient, greeting_account: Pubkey) -> Result<Greeting> {
     println!("\nFetching greeting account...");
     
-    let account_data = client.get_account_data(&greeting_account)?;
-    let greeting = Greeting::try_from_slice(&account_data)?;
+    let greeting = client.get_greeting(greeting_account).await?;
     
     println!("\nGreeting Account Data:");
     println!("  Owner: {}", greeting.owner);
     println!("  Count: {}", greeting.count);
     println!("  Message: \"{}\"", greeting.message);
     
+    Ok(greeting)
+}
+
+async fn inspect_account(client: &GreetingClient, account: Pubkey) -> Result<()> {
+    println!("\nInspecting {}...", account);
+    
+    let inspection = client.inspect(account).await?;
+    let data = &inspection.account.data;
+    
+    println!("\nOwner program: {}", inspection.account.owner);
+    println!("Executable: {}", inspection.account.executable);
+    println!(
+        "Lamports: {} (rent-exempt minimum for {} bytes: {})",
+        inspection.account.lamports,
+        Greeting::space(),
+        inspection.rent_exempt_minimum
+    );
+    println!("Data length: {} bytes", data.len());
+    
+    println!("\nLayout:");
+    for field in &inspection.fields {
+        let bytes = &data[field.range.clone()];
+        println!("  [{:>3}..{:>3}) {}", field.range.start, field.range.end, field.name);
+        if !bytes.is_empty() && bytes.iter().all(|&byte| byte == 0) && bytes.len() > HEX_ROW_LEN {
+            println!("    {:04x}  ({} zero bytes)", field.range.start, bytes.len());
+            continue;
+        }
+        for (row, chunk) in bytes.chunks(HEX_ROW_LEN).enumerate() {
+            let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+                .collect();
+            println!(
+                "    {:04x}  {:<width$}  {}",
+                field.range.start + row * HEX_ROW_LEN,
+                hex.join(" "),
+                ascii,
+                width = HEX_ROW_LEN * 3 - 1
+            );
+        }
+    }
+    
+    match &inspection.greeting {
+        Some(greeting) => {
+            println!("\nDecoded (ignoring bytes after the message):");
+            println!("  Owner: {}", greeting.owner);
+            println!("  Count: {}", greeting.count);
+            println!("  Message: \"{}\"", greeting.message);
+        }
+        None => println!("\nData does not decode as a greeting"),
+    }
+    
+    if inspection.problems.is_empty() {
+        println!("\nNo problems found");
+    } else {
+        println!("\nDiagnosis:");
+        for problem in &inspection.problems {
+            println!("  - {}", problem);
+        }
+    }
+    
     Ok(())
-}
\ No newline at end of file
+}
+
+async fn show_history(client: &GreetingClient, greeting_account: Pubkey, limit: usize) -> Result<()> {
+    println!("\nFetching history for {}...", greeting_account);
+    
+    let entries = client.history(greeting_account, Some(limit)).await?;
+    if entries.is_empty() {
+        println!("No transactions found");
+        return Ok(());
+    }
+    
+    for entry in entries {
+        let time = entry
+            .block_time
+            .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+            .map_or_else(|| "unknown time".to_string(), |time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string());
+        let status = match (&entry.error, entry.hello_error()) {
+            (None, _) => "OK".to_string(),
+            (Some(_), Some(hello_error)) => format!("FAILED: {}", hello_error),
+            (Some(error), None) => format!("FAILED: {}", error),
+        };
+        
+        println!("\n{}  slot {}  {}", time, entry.slot, status);
+        println!("  Signature: {}", entry.signature);
+        
+        if entry.instructions.is_empty() {
+            println!("  (no hello-account instructions; the account was only referenced)");
+        }
+        for instruction in &entry.instructions {
+            let via = if instruction.inner { " (via CPI)" } else { "" };
+            match &instruction.operation {
+                GreetingOperation::Initialize { payer, owner, .. } => {
+                    println!("  #{}{} Initialize: paid by {}, owner {}", instruction.index, via, payer, owner)
+                }
+                GreetingOperation::UpdateMessage { owner, message, .. } => {
+                    println!("  #{}{} UpdateMessage by {}: \"{}\"", instruction.index, via, owner, message)
+                }
+                GreetingOperation::IncrementOnly { owner, .. } => {
+                    println!("  #{}{} IncrementOnly by {}", instruction.index, via, owner)
+                }
+                GreetingOperation::Close { owner, destination, .. } => {
+                    println!("  #{}{} Close by {}, lamports to {}", instruction.index, via, owner, destination)
+                }
+                GreetingOperation::Undecodable { data } => {
+                    println!("  #{}{} Undecodable instruction data ({} bytes)", instruction.index, via, data.len())
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
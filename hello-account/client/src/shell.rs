@@ -0,0 +1,260 @@
+use anyhow::{anyhow, bail, Result};
+use clap::{CommandFactory, Parser};
+use rustyline::{
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    history::FileHistory,
+    Editor, Helper, Highlighter, Hinter, Validator,
+};
+use std::{collections::BTreeMap, fs};
+
+use crate::{address_book::AddressBook, config::config_dir, execute, get_greeting, Commands, Context};
+
+const HISTORY_FILE: &str = "history";
+
+// Session variable holding the greeting account the last command touched
+const CURRENT_GREETING: &str = "greeting";
+
+const BUILTINS: &[(&str, &str)] = &[
+    ("use", "use <account>        make <account> (address or alias) the current greeting"),
+    ("set", "set <name> <value>   define $name"),
+    ("unset", "unset <name>         remove $name"),
+    ("vars", "vars                 list session variables"),
+    ("help", "help [command]       list commands, or show help for one"),
+    ("exit", "exit                 leave the shell (also `quit` or Ctrl-D)"),
+    ("quit", ""),
+];
+
+// Commands that act on a greeting and fall back to $greeting without --account
+const ACCOUNT_COMMANDS: &[&str] = &["update", "increment", "close", "get", "inspect", "history"];
+
+/// Parses a shell line with the same subcommands as the CLI itself.
+#[derive(Parser)]
+#[command(no_binary_name = true, disable_help_subcommand = true)]
+struct ShellLine {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Helper, Hinter, Highlighter, Validator)]
+struct ShellHelper {
+    vars: BTreeMap<String, String>,
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &rustyline::Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &line[start..];
+        let first_word = line[..start].split_whitespace().next();
+
+        let candidates: Vec<String> = if let Some(name) = word.strip_prefix('$') {
+            self.vars
+                .keys()
+                .filter(|var| var.starts_with(name))
+                .map(|var| format!("${}", var))
+                .collect()
+        } else if let Some(command) = first_word {
+            match ShellLine::command().find_subcommand(command) {
+                Some(subcommand) if word.starts_with('-') => subcommand
+                    .get_arguments()
+                    .filter_map(|arg| arg.get_long())
+                    .map(|long| format!("--{}", long))
+                    .filter(|flag| flag.starts_with(word))
+                    .collect(),
+                _ => Vec::new(),
+            }
+        } else {
+            command_names().into_iter().filter(|name| name.starts_with(word)).collect()
+        };
+
+        let pairs = candidates
+            .into_iter()
+            .map(|candidate| Pair {
+                display: candidate.clone(),
+                replacement: candidate,
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+fn command_names() -> Vec<String> {
+    let mut names: Vec<String> = ShellLine::command()
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .filter(|name| name != "shell")
+        .collect();
+    names.extend(BUILTINS.iter().map(|(name, _)| name.to_string()));
+    names.sort();
+    names
+}
+
+/// Runs the interactive shell until `exit`, `quit` or end of input.
+pub async fn run(ctx: &Context) -> Result<()> {
+    let mut editor: Editor<ShellHelper, FileHistory> = Editor::new()?;
+    editor.set_helper(Some(ShellHelper { vars: BTreeMap::new() }));
+
+    let config_dir = config_dir();
+    fs::create_dir_all(&config_dir)?;
+    let history_path = config_dir.join(HISTORY_FILE);
+    // A missing history file just means this is the first session
+    let _ = editor.load_history(&history_path);
+
+    println!("\nHello Account shell. Type `help` for commands, `exit` to leave.");
+
+    loop {
+        let prompt = prompt(current_greeting(&editor).as_deref());
+
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line)?;
+
+        match run_line(ctx, &mut editor, line).await {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(err) => println!("Error: {:#}", err),
+        }
+    }
+
+    editor.save_history(&history_path)?;
+    Ok(())
+}
+
+/// Executes one line; returns `false` when the session should end.
+async fn run_line(ctx: &Context, editor: &mut Editor<ShellHelper, FileHistory>, line: &str) -> Result<bool> {
+    let helper = editor.helper_mut().expect("helper is set");
+    let words = shlex::split(line).ok_or_else(|| anyhow!("Unbalanced quotes"))?;
+    let words = words
+        .into_iter()
+        .map(|word| expand(&helper.vars, &word))
+        .collect::<Result<Vec<_>>>()?;
+
+    match words.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["exit"] | ["quit"] => return Ok(false),
+        ["help"] => {
+            println!("Commands: {}", command_names().join(", "));
+            for (_, usage) in BUILTINS.iter().filter(|(_, usage)| !usage.is_empty()) {
+                println!("  {}", usage);
+            }
+            println!("  <command> --help     show options for a client command");
+            println!("Variables are referenced as $name; ${} follows the last account used.", CURRENT_GREETING);
+        }
+        ["help", command] => {
+            ShellLine::try_parse_from([command, "--help"]).map(|_| ()).unwrap_or_else(|err| print!("{}", err));
+        }
+        ["use", account] => {
+            let account = AddressBook::load()?.resolve(account)?;
+            helper.vars.insert(CURRENT_GREETING.to_string(), account.to_string());
+        }
+        ["set", name, value] => {
+            helper.vars.insert(name.to_string(), value.to_string());
+        }
+        ["unset", name] => {
+            helper.vars.remove(*name);
+        }
+        ["vars"] => {
+            for (name, value) in &helper.vars {
+                println!("  ${} = {}", name, value);
+            }
+        }
+        [builtin, ..] if BUILTINS.iter().any(|(name, _)| name == builtin) => {
+            bail!("Usage: {}", BUILTINS.iter().find(|(name, _)| name == builtin).map_or("", |(_, usage)| usage));
+        }
+        _ => {
+            let mut words = words;
+            if let (Some(command), Some(current)) = (words.first(), helper.vars.get(CURRENT_GREETING)) {
+                let has_account = words.iter().any(|word| is_account_flag(word));
+                if ACCOUNT_COMMANDS.contains(&command.as_str()) && !has_account {
+                    words.push("--account".to_string());
+                    words.push(current.clone());
+                }
+            }
+
+            let command = match ShellLine::try_parse_from(&words) {
+                Ok(parsed) => parsed.command,
+                Err(err) => {
+                    print!("{}", err);
+                    return Ok(true);
+                }
+            };
+
+            let shows_result = matches!(command, Commands::Init { .. } | Commands::Update { .. } | Commands::Increment { .. });
+            let closes = matches!(command, Commands::Close { .. });
+            if let Some(greeting) = execute(ctx, command).await? {
+                if closes {
+                    // A closed greeting is no longer anything to fall back to
+                    helper.vars.retain(|name, value| name != CURRENT_GREETING || *value != greeting.to_string());
+                } else {
+                    helper.vars.insert(CURRENT_GREETING.to_string(), greeting.to_string());
+                }
+                if shows_result {
+                    get_greeting(&ctx.client, greeting).await?;
+                }
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+/// Whether `word` passes `--account`. Only the exact flags count, so that a
+/// message such as `-amazing` is not mistaken for one.
+fn is_account_flag(word: &str) -> bool {
+    word == "-a" || word == "--account" || word.starts_with("--account=")
+}
+
+/// Shows the first characters of the current greeting, which may be any text
+/// set with `set greeting`.
+fn prompt(greeting: Option<&str>) -> String {
+    match greeting {
+        Some(greeting) => format!("hello [{}…]> ", greeting.chars().take(8).collect::<String>()),
+        None => "hello> ".to_string(),
+    }
+}
+
+fn current_greeting(editor: &Editor<ShellHelper, FileHistory>) -> Option<String> {
+    editor.helper()?.vars.get(CURRENT_GREETING).cloned()
+}
+
+/// Replaces a word of the form `$name` with the variable's value.
+fn expand(vars: &BTreeMap<String, String>, word: &str) -> Result<String> {
+    match word.strip_prefix('$') {
+        Some(name) => vars.get(name).cloned().ok_or_else(|| anyhow!("Undefined variable ${}", name)),
+        None => Ok(word.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_exact_account_flags_are_recognized() {
+        for word in ["--account", "--account=alice", "-a"] {
+            assert!(is_account_flag(word), "{}", word);
+        }
+        for word in ["--all", "--accounts", "alice", "-m", "--message=-a", "-amazing", "-ab"] {
+            assert!(!is_account_flag(word), "{}", word);
+        }
+    }
+
+    #[test]
+    fn prompts_truncate_by_character() {
+        assert_eq!(prompt(None), "hello> ");
+        assert_eq!(prompt(Some("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin")), "hello [9xQeWvG8…]> ");
+        assert_eq!(prompt(Some("aéééé")), "hello [aéééé…]> ");
+        assert_eq!(prompt(Some("ééééééééé")), "hello [éééééééé…]> ");
+    }
+}
@@ -0,0 +1,57 @@
+use anyhow::{anyhow, Context, Result};
+use solana_sdk::signature::{keypair_from_seed_phrase_and_passphrase, read_keypair, read_keypair_file, Keypair};
+use std::io;
+
+use crate::keystore;
+
+/// Where a signing keypair is loaded from.
+///
+/// - `prompt://` asks for a seed phrase and an optional passphrase
+/// - `stdin://` reads a JSON keypair (as written by `solana-keygen`) from stdin
+/// - `file://<path>` or a bare path reads a JSON keypair file; `~` is expanded
+/// - `keystore://<name>` unlocks an entry of the encrypted keystore (`keys`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignerSource {
+    Prompt,
+    Stdin,
+    File(String),
+    Keystore(String),
+}
+
+impl SignerSource {
+    pub fn parse(uri: &str) -> Result<Self> {
+        match uri.split_once("://") {
+            Some(("prompt", "")) => Ok(Self::Prompt),
+            Some(("stdin", "")) => Ok(Self::Stdin),
+            Some(("file", path)) if !path.is_empty() => Ok(Self::File(path.to_string())),
+            Some(("keystore", name)) if !name.is_empty() => Ok(Self::Keystore(name.to_string())),
+            Some((scheme, _)) => Err(anyhow!(
+                "Unsupported signer `{}` (expected a path, file://, keystore://, prompt:// or stdin://)",
+                scheme
+            )),
+            None => Ok(Self::File(uri.to_string())),
+        }
+    }
+
+    /// Loads the keypair; `name` identifies the role ("fee payer", "owner")
+    /// in prompts and error messages.
+    pub fn load(&self, name: &str) -> Result<Keypair> {
+        match self {
+            Self::Prompt => {
+                let seed_phrase = rpassword::prompt_password(format!("Seed phrase for {}: ", name))?;
+                let passphrase = rpassword::prompt_password(format!("Passphrase for {} (empty for none): ", name))?;
+                keypair_from_seed_phrase_and_passphrase(seed_phrase.trim(), &passphrase)
+                    .map_err(|err| anyhow!("Invalid seed phrase for {}: {}", name, err))
+            }
+            Self::Stdin => read_keypair(&mut io::stdin())
+                .map_err(|err| anyhow!("Failed to read {} keypair from stdin: {}", name, err)),
+            Self::File(path) => {
+                let path = shellexpand::tilde(path).to_string();
+                read_keypair_file(&path)
+                    .map_err(|err| anyhow!("{}", err))
+                    .with_context(|| format!("Failed to read {} keypair file {}", name, path))
+            }
+            Self::Keystore(entry) => keystore::unlock(entry).with_context(|| format!("Failed to unlock {} keypair", name)),
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use std::{fs, path::Path, str::FromStr};
+
+use hello_account::state::{Greeting, MAX_MESSAGE_LEN};
+use hello_account_client::{instructions, GreetingClient};
+
+// What `Initialize` writes, so a never-updated greeting needs no replay
+const INITIAL_MESSAGE: &str = "Hello, Solana!";
+
+// Small enough that a transaction of them stays within size and compute limits
+const INCREMENTS_PER_TRANSACTION: usize = 32;
+
+// Counts above this take too many transactions to replay; use fixtures instead
+const MAX_REPLAY_COUNT: u64 = 10_000;
+
+/// Greetings of one program as they were on the source cluster.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub program_id: String,
+    pub source: String,
+    pub slot: u64,
+    pub greetings: Vec<SnapshotGreeting>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SnapshotGreeting {
+    pub address: String,
+    pub lamports: u64,
+    pub owner: String,
+    pub count: u64,
+    pub message: String,
+    /// Raw account data, base64, for byte-exact fixtures
+    pub data: String,
+}
+
+impl SnapshotGreeting {
+    fn new(address: Pubkey, account: &Account) -> Result<Self> {
+        let greeting = Greeting::load(&account.data)
+            .with_context(|| format!("{} is not a valid greeting", address))?;
+
+        Ok(Self {
+            address: address.to_string(),
+            lamports: account.lamports,
+            owner: greeting.owner.to_string(),
+            count: greeting.count,
+            message: greeting.message,
+            data: BASE64.encode(&account.data),
+        })
+    }
+}
+
+/// Writes the given greetings, or every greeting of the program when
+/// `accounts` is empty, to `output`.
+pub async fn export(client: &GreetingClient, accounts: &[Pubkey], output: &str) -> Result<()> {
+    let mut greetings = Vec::new();
+
+    if accounts.is_empty() {
+        println!("\nFetching every greeting of program {}...", client.program_id());
+        for (address, account) in client.get_program_greetings().await? {
+            match SnapshotGreeting::new(address, &account) {
+                Ok(greeting) => greetings.push(greeting),
+                // A closed account keeps its size until it is garbage collected
+                Err(err) => println!("Skipping {:#}", err),
+            }
+        }
+    } else {
+        for &address in accounts {
+            let account = client.get_raw_account(address).await?;
+            if account.owner != client.program_id() {
+                bail!("{} is owned by {}, not program {}", address, account.owner, client.program_id());
+            }
+            greetings.push(SnapshotGreeting::new(address, &account)?);
+        }
+    }
+
+    let snapshot = Snapshot {
+        program_id: client.program_id().to_string(),
+        source: client.rpc().url(),
+        slot: client.rpc().get_slot().await?,
+        greetings,
+    };
+
+    fs::write(output, serde_json::to_string_pretty(&snapshot)?)
+        .with_context(|| format!("Failed to write snapshot {}", output))?;
+    println!("Exported {} greeting(s) to {}", snapshot.greetings.len(), output);
+
+    Ok(())
+}
+
+fn read_snapshot(path: &str) -> Result<Snapshot> {
+    let contents = fs::read_to_string(path).with_context(|| format!("Failed to read snapshot {}", path))?;
+    serde_json::from_str(&contents).with_context(|| format!("Failed to parse snapshot {}", path))
+}
+
+/// Re-creates every greeting of the snapshot at a fresh address, owned by
+/// `owner`, by replaying `Initialize`, `IncrementOnly` and `UpdateMessage`.
+pub async fn replay(client: &GreetingClient, payer: &Keypair, owner: &Keypair, snapshot_path: &str) -> Result<()> {
+    let snapshot = read_snapshot(snapshot_path)?;
+    println!("\nReplaying {} greeting(s) from {} (slot {})", snapshot.greetings.len(), snapshot.source, snapshot.slot);
+
+    let mut failed = 0;
+    for greeting in &snapshot.greetings {
+        match replay_greeting(client, payer, owner, greeting).await {
+            Ok(address) => println!("{} -> {}", greeting.address, address),
+            Err(err) => {
+                failed += 1;
+                println!("{} failed: {:#}", greeting.address, err);
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{} of {} greeting(s) could not be replayed", failed, snapshot.greetings.len());
+    }
+    Ok(())
+}
+
+async fn replay_greeting(
+    client: &GreetingClient,
+    payer: &Keypair,
+    owner: &Keypair,
+    greeting: &SnapshotGreeting,
+) -> Result<Pubkey> {
+    if greeting.owner != owner.pubkey().to_string() {
+        println!("{}: owner {} replaced by {}", greeting.address, greeting.owner, owner.pubkey());
+    }
+
+    let greeting_keypair = Keypair::new();
+    let address = greeting_keypair.pubkey();
+    let transactions = replay_plan(client.program_id(), owner.pubkey(), address, greeting)?;
+    client.initialize_with_keypair(payer, &greeting_keypair, owner.pubkey()).await?;
+    for instructions in transactions {
+        client.send(&instructions, payer, &[payer, owner]).await?;
+    }
+
+    Ok(address)
+}
+
+/// The transactions that bring a freshly initialized greeting at `address` to
+/// the count and message of `greeting`: `count - 1` increments, then the
+/// update that sets the message and counts once more.
+fn replay_plan(program_id: Pubkey, owner: Pubkey, address: Pubkey, greeting: &SnapshotGreeting) -> Result<Vec<Vec<Instruction>>> {
+    // UpdateMessage is the only way to set the message and it also counts
+    if greeting.count == 0 && greeting.message != INITIAL_MESSAGE {
+        bail!("count 0 with a custom message cannot be replayed; use --fixtures");
+    }
+    if greeting.count > MAX_REPLAY_COUNT {
+        bail!("count {} needs too many transactions to replay (max {}); use --fixtures", greeting.count, MAX_REPLAY_COUNT);
+    }
+    if greeting.message.len() > MAX_MESSAGE_LEN {
+        bail!("Message too long! Max length: {}", MAX_MESSAGE_LEN);
+    }
+    if greeting.count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let increments = vec![instructions::increment_only(program_id, owner, address); greeting.count as usize - 1];
+    let mut transactions: Vec<Vec<Instruction>> = increments.chunks(INCREMENTS_PER_TRANSACTION).map(<[_]>::to_vec).collect();
+    transactions.push(vec![instructions::update_message(program_id, owner, address, &greeting.message)]);
+    Ok(transactions)
+}
+
+/// Writes one `solana-test-validator --account` file per greeting, keeping
+/// the original addresses and bytes but owned by `program_id`.
+pub fn write_fixtures(program_id: Pubkey, snapshot_path: &str, dir: &str) -> Result<()> {
+    let snapshot = read_snapshot(snapshot_path)?;
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir))?;
+
+    let mut args = Vec::with_capacity(snapshot.greetings.len());
+    for greeting in &snapshot.greetings {
+        Pubkey::from_str(&greeting.address).with_context(|| format!("Invalid address {}", greeting.address))?;
+        let space = BASE64.decode(&greeting.data)?.len();
+
+        // The format `solana account --output json` writes
+        let fixture = json!({
+            "pubkey": greeting.address,
+            "account": {
+                "lamports": greeting.lamports,
+                "data": [greeting.data, "base64"],
+                "owner": program_id.to_string(),
+                "executable": false,
+                "rentEpoch": 0,
+                "space": space,
+            },
+        });
+
+        let path = Path::new(dir).join(format!("{}.json", greeting.address));
+        fs::write(&path, serde_json::to_string_pretty(&fixture)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        args.push(format!("--account {} {}", greeting.address, path.display()));
+    }
+
+    println!("\nWrote {} fixture(s) to {}", args.len(), dir);
+    println!("Load them with:\n  solana-test-validator {}", args.join(" "));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hello_account::{instruction::HelloInstruction, processor::Processor};
+    use solana_account_decoder::UiAccount;
+    use solana_program_test::{processor, ProgramTest};
+    use solana_sdk::transaction::Transaction;
+
+    fn account(program_id: Pubkey, greeting: &Greeting) -> Account {
+        let mut data = vec![0; Greeting::space()];
+        greeting.store(&mut data).unwrap();
+        Account { lamports: 1_000_000, data, owner: program_id, executable: false, rent_epoch: 0 }
+    }
+
+    /// Exports `greeting` and reads it back as `import` would.
+    fn round_trip(program_id: Pubkey, address: Pubkey, greeting: &Greeting) -> SnapshotGreeting {
+        let snapshot = Snapshot {
+            program_id: program_id.to_string(),
+            source: "http://localhost:8899".to_string(),
+            slot: 42,
+            greetings: vec![SnapshotGreeting::new(address, &account(program_id, greeting)).unwrap()],
+        };
+        let mut parsed: Snapshot = serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+        parsed.greetings.pop().unwrap()
+    }
+
+    /// Applies `transactions` to the greeting `Initialize` creates, as the
+    /// program would.
+    fn apply(owner: Pubkey, transactions: &[Vec<Instruction>]) -> Greeting {
+        let mut greeting = Greeting { owner, count: 0, message: INITIAL_MESSAGE.to_string() };
+        for instruction in transactions.iter().flatten() {
+            match HelloInstruction::unpack(&instruction.data).unwrap() {
+                HelloInstruction::IncrementOnly => greeting.count += 1,
+                HelloInstruction::UpdateMessage { message } => {
+                    greeting.count += 1;
+                    greeting.message = message;
+                }
+                other => panic!("unexpected {:?} in a replay", other),
+            }
+        }
+        greeting
+    }
+
+    #[test]
+    fn exported_greetings_replay_to_the_same_state() {
+        let (program_id, owner, address) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        for (count, message) in [(0, INITIAL_MESSAGE), (1, "Hi"), (2, ""), (70, "Seventy")] {
+            let greeting = Greeting { owner: Pubkey::new_unique(), count, message: message.to_string() };
+            let exported = round_trip(program_id, Pubkey::new_unique(), &greeting);
+            assert_eq!((exported.count, exported.message.as_str()), (count, message));
+
+            let plan = replay_plan(program_id, owner, address, &exported).unwrap();
+            assert!(plan.iter().all(|instructions| instructions.len() <= INCREMENTS_PER_TRANSACTION));
+            assert_eq!(apply(owner, &plan), Greeting { owner, ..greeting }, "count {}", count);
+        }
+    }
+
+    #[test]
+    fn unreplayable_greetings_are_rejected() {
+        let (program_id, owner, address) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        for (count, message) in [(0, "Custom"), (MAX_REPLAY_COUNT + 1, "Hi")] {
+            let greeting = Greeting { owner, count, message: message.to_string() };
+            let exported = round_trip(program_id, address, &greeting);
+            assert!(replay_plan(program_id, owner, address, &exported).is_err(), "count {}", count);
+        }
+    }
+
+    /// The wrapper `solana account --output json` writes around the account.
+    #[derive(Deserialize)]
+    struct Fixture {
+        pubkey: String,
+        account: UiAccount,
+    }
+
+    #[tokio::test]
+    async fn fixtures_load_as_program_accounts() {
+        let dir = std::env::temp_dir().join(format!("hello-account-fixtures-{}", std::process::id()));
+        let source_program = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let owner = Keypair::new();
+        let address = Pubkey::new_unique();
+        let greeting = Greeting { owner: owner.pubkey(), count: 5, message: "From a fixture".to_string() };
+
+        let snapshot = Snapshot {
+            program_id: source_program.to_string(),
+            source: "http://localhost:8899".to_string(),
+            slot: 42,
+            greetings: vec![SnapshotGreeting::new(address, &account(source_program, &greeting)).unwrap()],
+        };
+        let snapshot_path = dir.join("snapshot.json");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&snapshot_path, serde_json::to_string(&snapshot).unwrap()).unwrap();
+        let fixtures = dir.join("fixtures");
+        write_fixtures(program_id, snapshot_path.to_str().unwrap(), fixtures.to_str().unwrap()).unwrap();
+
+        let contents = fs::read_to_string(fixtures.join(format!("{}.json", address))).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let fixture: Fixture = serde_json::from_str(&contents).unwrap();
+        assert_eq!(fixture.pubkey, address.to_string());
+        let fixture_account: Account = fixture.account.decode().expect("fixture decodes as an account");
+        assert_eq!(fixture_account.owner, program_id);
+
+        // The program accepts the loaded greeting as its own
+        let mut program_test = ProgramTest::new("hello_account", program_id, processor!(Processor::process));
+        program_test.add_account(address, fixture_account);
+        let (mut banks, payer, blockhash) = program_test.start().await;
+        let transaction = Transaction::new_signed_with_payer(
+            &[instructions::increment_only(program_id, owner.pubkey(), address)],
+            Some(&payer.pubkey()),
+            &[&payer, &owner],
+            blockhash,
+        );
+        banks.process_transaction(transaction).await.unwrap();
+
+        let loaded = banks.get_account(address).await.unwrap().expect("fixture account exists");
+        assert_eq!(Greeting::load(&loaded.data).unwrap(), Greeting { count: 6, ..greeting });
+    }
+}
@@ -0,0 +1,370 @@
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+};
+use solana_sdk::{
+    address_lookup_table::AddressLookupTableAccount,
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, CompileError, Message, VersionedMessage},
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+    transaction::VersionedTransaction,
+};
+use std::{
+    fmt,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::error::{ClientError, Result};
+
+// Upper bound the runtime allows for a single transaction
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+// Percentile of recent prioritization fees used by `PriorityFee::Auto`
+const AUTO_PRIORITY_FEE_PERCENTILE: usize = 75;
+
+const REBROADCAST_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Compute unit price to attach to every transaction, in micro-lamports.
+#[derive(Clone, Copy, Debug)]
+pub enum PriorityFee {
+    /// Derived from `getRecentPrioritizationFees` for the written accounts
+    Auto,
+    Fixed(u64),
+}
+
+impl FromStr for PriorityFee {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(Self::Auto);
+        }
+        s.parse()
+            .map(Self::Fixed)
+            .map_err(|_| format!("expected `auto` or micro-lamports per compute unit, got `{}`", s))
+    }
+}
+
+/// Compute unit limit to request for every transaction.
+#[derive(Clone, Copy, Debug)]
+pub enum ComputeUnitLimit {
+    /// Sized from a simulation of the transaction
+    Auto,
+    Fixed(u32),
+}
+
+impl FromStr for ComputeUnitLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(Self::Auto);
+        }
+        let units: u32 = s
+            .parse()
+            .map_err(|_| format!("expected `auto` or a number of compute units, got `{}`", s))?;
+        if units > MAX_COMPUTE_UNIT_LIMIT {
+            return Err(format!("compute unit limit cannot exceed {}", MAX_COMPUTE_UNIT_LIMIT));
+        }
+        Ok(Self::Fixed(units))
+    }
+}
+
+/// Milestones reported while a transaction is being landed.
+#[derive(Clone, Debug)]
+pub enum SendProgress {
+    PriorityFee(u64),
+    ComputeUnitLimit(u32),
+    Sent { signature: Signature, attempt: usize },
+    Rebroadcast(Signature),
+    BlockhashExpired,
+}
+
+impl fmt::Display for SendProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PriorityFee(micro_lamports) => write!(f, "Priority fee: {} micro-lamports/CU", micro_lamports),
+            Self::ComputeUnitLimit(units) => write!(f, "Compute unit limit: {}", units),
+            Self::Sent { signature, attempt } => {
+                write!(f, "Sent {} (attempt {}), awaiting confirmation...", signature, attempt)
+            }
+            Self::Rebroadcast(signature) => write!(f, "Rebroadcast {}", signature),
+            Self::BlockhashExpired => write!(f, "Blockhash expired, re-signing with a fresh one..."),
+        }
+    }
+}
+
+pub type ProgressHandler = Arc<dyn Fn(&SendProgress) + Send + Sync>;
+
+/// How transactions are priced and how long we keep trying to land them.
+#[derive(Clone)]
+pub struct SendConfig {
+    pub priority_fee: Option<PriorityFee>,
+    pub compute_unit_limit: Option<ComputeUnitLimit>,
+    pub timeout: Duration,
+    /// Called for every `SendProgress` milestone
+    pub progress: Option<ProgressHandler>,
+    /// Tables to compile v0 messages against; legacy transactions are sent
+    /// when empty
+    pub lookup_tables: Vec<AddressLookupTableAccount>,
+    /// How often signature statuses are polled while awaiting confirmation
+    pub status_poll_interval: Duration,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        Self {
+            priority_fee: None,
+            compute_unit_limit: None,
+            timeout: Duration::from_secs(90),
+            progress: None,
+            lookup_tables: Vec::new(),
+            status_poll_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+impl fmt::Debug for SendConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendConfig")
+            .field("priority_fee", &self.priority_fee)
+            .field("compute_unit_limit", &self.compute_unit_limit)
+            .field("timeout", &self.timeout)
+            .field("progress", &self.progress.is_some())
+            .field("lookup_tables", &self.lookup_tables.iter().map(|table| table.key).collect::<Vec<_>>())
+            .field("status_poll_interval", &self.status_poll_interval)
+            .finish()
+    }
+}
+
+impl SendConfig {
+    fn report(&self, progress: SendProgress) {
+        if let Some(handler) = &self.progress {
+            handler(&progress);
+        }
+    }
+}
+
+/// Builds, prices, signs and sends a transaction, then keeps rebroadcasting it
+/// until it is confirmed or `config.timeout` elapses. When the blockhash
+/// expires the transaction is re-signed with a fresh one; every signature sent
+/// so far is still watched, since an earlier copy may land late.
+pub async fn send_instructions(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &dyn Signer,
+    signers: &[&dyn Signer],
+    config: &SendConfig,
+) -> Result<Signature> {
+    let instructions = with_compute_budget(client, instructions, payer, config).await?;
+
+    // The same key may fill several roles (payer and owner); sign once per key
+    let mut unique_signers: Vec<&dyn Signer> = Vec::with_capacity(signers.len());
+    for signer in signers {
+        if !unique_signers.iter().any(|s| s.pubkey() == signer.pubkey()) {
+            unique_signers.push(*signer);
+        }
+    }
+
+    let deadline = Instant::now() + config.timeout;
+    let commitment = client.commitment();
+
+    let mut sent: Vec<Signature> = Vec::new();
+
+    loop {
+        let (blockhash, last_valid_block_height) =
+            client.get_latest_blockhash_with_commitment(commitment).await?;
+        let message = compile_message(&instructions, &payer.pubkey(), blockhash, &config.lookup_tables)?;
+        let transaction = VersionedTransaction::try_new(message, unique_signers.as_slice())?;
+
+        // Only the first copy goes through preflight so that program errors
+        // surface immediately; rebroadcasts must not be rejected as duplicates.
+        let signature = client
+            .send_transaction_with_config(
+                &transaction,
+                RpcSendTransactionConfig {
+                    skip_preflight: !sent.is_empty(),
+                    preflight_commitment: Some(commitment.commitment),
+                    max_retries: Some(0),
+                    ..RpcSendTransactionConfig::default()
+                },
+            )
+            .await?;
+        sent.push(signature);
+        config.report(SendProgress::Sent { signature, attempt: sent.len() });
+
+        let mut last_broadcast = Instant::now();
+
+        loop {
+            tokio::time::sleep(config.status_poll_interval).await;
+
+            let statuses = client.get_signature_statuses(&sent).await?.value;
+            for (signature, status) in sent.iter().zip(statuses) {
+                let Some(status) = status else { continue };
+                if let Some(error) = status.err {
+                    return Err(ClientError::TransactionFailed {
+                        signature: *signature,
+                        error,
+                    });
+                }
+                if status.satisfies_commitment(commitment) {
+                    return Ok(*signature);
+                }
+            }
+
+            if Instant::now() >= deadline {
+                return Err(ClientError::ConfirmationTimeout {
+                    signatures: sent,
+                    timeout: config.timeout,
+                });
+            }
+
+            if client.get_block_height().await? > last_valid_block_height {
+                config.report(SendProgress::BlockhashExpired);
+                break;
+            }
+
+            if last_broadcast.elapsed() >= REBROADCAST_INTERVAL {
+                client
+                    .send_transaction_with_config(
+                        &transaction,
+                        RpcSendTransactionConfig {
+                            skip_preflight: true,
+                            max_retries: Some(0),
+                            ..RpcSendTransactionConfig::default()
+                        },
+                    )
+                    .await?;
+                last_broadcast = Instant::now();
+                config.report(SendProgress::Rebroadcast(signature));
+            }
+        }
+    }
+}
+
+/// Prepends the compute budget instructions requested by `config`.
+async fn with_compute_budget(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &dyn Signer,
+    config: &SendConfig,
+) -> Result<Vec<Instruction>> {
+    let mut budget = Vec::new();
+
+    if let Some(priority_fee) = config.priority_fee {
+        let micro_lamports = match priority_fee {
+            PriorityFee::Fixed(micro_lamports) => micro_lamports,
+            PriorityFee::Auto => recent_priority_fee(client, instructions).await?,
+        };
+        config.report(SendProgress::PriorityFee(micro_lamports));
+        budget.push(ComputeBudgetInstruction::set_compute_unit_price(micro_lamports));
+    }
+
+    // Paying a priority fee on the default 200k units per instruction is
+    // wasteful, so size the limit whenever a fee is set and no limit is given.
+    let compute_unit_limit = config
+        .compute_unit_limit
+        .or(config.priority_fee.map(|_| ComputeUnitLimit::Auto));
+
+    if let Some(compute_unit_limit) = compute_unit_limit {
+        let units = match compute_unit_limit {
+            ComputeUnitLimit::Fixed(units) => units,
+            ComputeUnitLimit::Auto => {
+                simulate_compute_units(client, &budget, instructions, payer, &config.lookup_tables).await?
+            }
+        };
+        config.report(SendProgress::ComputeUnitLimit(units));
+        budget.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+    }
+
+    budget.extend_from_slice(instructions);
+    Ok(budget)
+}
+
+async fn recent_priority_fee(client: &RpcClient, instructions: &[Instruction]) -> Result<u64> {
+    let writable: Vec<_> = instructions
+        .iter()
+        .flat_map(|ix| ix.accounts.iter())
+        .filter(|meta| meta.is_writable)
+        .map(|meta| meta.pubkey)
+        .collect();
+
+    let mut fees: Vec<u64> = client
+        .get_recent_prioritization_fees(&writable)
+        .await?
+        .into_iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect();
+
+    if fees.is_empty() {
+        return Ok(0);
+    }
+
+    fees.sort_unstable();
+    let index = (fees.len() - 1) * AUTO_PRIORITY_FEE_PERCENTILE / 100;
+    Ok(fees[index])
+}
+
+async fn simulate_compute_units(
+    client: &RpcClient,
+    budget: &[Instruction],
+    instructions: &[Instruction],
+    payer: &dyn Signer,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<u32> {
+    let mut simulated = budget.to_vec();
+    simulated.push(ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT));
+    simulated.extend_from_slice(instructions);
+
+    // Unsigned: the node skips signature checks and replaces the blockhash
+    let message = compile_message(&simulated, &payer.pubkey(), Hash::default(), lookup_tables)?;
+    let transaction = VersionedTransaction {
+        signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+        message,
+    };
+    let result = client
+        .simulate_transaction_with_config(
+            &transaction,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                commitment: Some(client.commitment()),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .await?
+        .value;
+
+    if let Some(error) = result.err {
+        return Err(ClientError::SimulationFailed {
+            error,
+            logs: result.logs.unwrap_or_default(),
+        });
+    }
+
+    let consumed = result.units_consumed.ok_or(ClientError::MissingUnitsConsumed)?;
+
+    // Leave 10% headroom: account state can change between simulation and execution
+    let units = consumed.saturating_add(consumed / 10);
+    Ok(units.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32)
+}
+
+/// Compiles a legacy message, or a v0 message that loads whatever accounts it
+/// can from `lookup_tables` when any are given.
+pub fn compile_message(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    blockhash: Hash,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> std::result::Result<VersionedMessage, CompileError> {
+    if lookup_tables.is_empty() {
+        return Ok(VersionedMessage::Legacy(Message::new_with_blockhash(instructions, Some(payer), &blockhash)));
+    }
+
+    Ok(VersionedMessage::V0(v0::Message::try_compile(payer, instructions, lookup_tables, blockhash)?))
+}
@@ -0,0 +1,361 @@
+//! Runs the client binary against a `solana-test-validator` with the
+//! program's SBF build preloaded, checking its output, its exit status and
+//! the state it leaves on chain.
+//!
+//! Needs `solana-test-validator` on `PATH` (or in `SOLANA_TEST_VALIDATOR`)
+//! and the SBF build; from `hello-account`:
+//!
+//! ```sh
+//! (cd program && cargo build-sbf)
+//! (cd client && cargo test --test e2e -- --include-ignored)
+//! ```
+//!
+//! `greeting_lifecycle` runs with every `cargo test`, and skips with a note
+//! where either is missing unless `HELLO_ACCOUNT_E2E=1` requires it, as CI
+//! should.
+
+use hello_account::{
+    error::HelloError,
+    state::{Greeting, MAX_MESSAGE_LEN},
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, write_keypair_file, Keypair, Signer},
+};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Child, Command, Output, Stdio},
+    sync::{Mutex, MutexGuard},
+    thread,
+    time::{Duration, Instant},
+};
+
+// Away from the defaults, so that a validator the developer runs is left alone
+const RPC_PORT: u16 = 18899;
+const FAUCET_PORT: u16 = 19900;
+const GOSSIP_PORT: u16 = 18001;
+const DYNAMIC_PORT_RANGE: &str = "18100-18200";
+
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_SBF_OUT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../program/target/deploy");
+const REQUIRE_ENV: &str = "HELLO_ACCOUNT_E2E";
+
+// Every validator binds the same ports, so tests take turns
+static VALIDATOR_PORTS: Mutex<()> = Mutex::new(());
+
+/// A validator running the program, killed on drop along with its ledger,
+/// and the client configuration each test runs the binary with.
+struct TestValidator {
+    child: Child,
+    dir: PathBuf,
+    program_id: Pubkey,
+    payer: PathBuf,
+    rpc: RpcClient,
+    _ports: MutexGuard<'static, ()>,
+}
+
+impl TestValidator {
+    fn start(name: &str) -> Self {
+        if let Err(missing) = prerequisites() {
+            panic!("{}", missing);
+        }
+        let ports = VALIDATOR_PORTS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let dir = env::temp_dir().join(format!("hello-account-e2e-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("temporary directory");
+
+        let program = sbf_program_path();
+        let program_id = Pubkey::new_unique();
+        let validator = validator_path();
+        let child = Command::new(&validator)
+            .arg("--ledger")
+            .arg(dir.join("ledger"))
+            .args(["--reset", "--quiet"])
+            .args(["--rpc-port", &RPC_PORT.to_string()])
+            .args(["--faucet-port", &FAUCET_PORT.to_string()])
+            .args(["--gossip-port", &GOSSIP_PORT.to_string()])
+            .args(["--dynamic-port-range", DYNAMIC_PORT_RANGE])
+            .arg("--bpf-program")
+            .arg(program_id.to_string())
+            .arg(&program)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap_or_else(|err| panic!("failed to start {}: {}", validator.display(), err));
+
+        let payer = dir.join("payer.json");
+        write_keypair_file(&Keypair::new(), &payer).expect("payer keypair written");
+        let rpc = RpcClient::new_with_commitment(format!("http://127.0.0.1:{}", RPC_PORT), CommitmentConfig::confirmed());
+        let mut validator = Self { child, dir, program_id, payer, rpc, _ports: ports };
+        validator.wait_until_ready();
+        validator
+    }
+
+    fn wait_until_ready(&mut self) {
+        let started = Instant::now();
+        while self.rpc.get_latest_blockhash().is_err() {
+            if let Some(status) = self.child.try_wait().expect("validator status") {
+                panic!("solana-test-validator exited with {} before serving RPC", status);
+            }
+            assert!(started.elapsed() < STARTUP_TIMEOUT, "solana-test-validator not ready after {:?}", STARTUP_TIMEOUT);
+            thread::sleep(Duration::from_millis(250));
+        }
+    }
+
+    fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", RPC_PORT)
+    }
+
+    /// Writes a new keypair next to the ledger and returns its path.
+    fn keypair(&self, name: &str) -> PathBuf {
+        let path = self.dir.join(format!("{}.json", name));
+        write_keypair_file(&Keypair::new(), &path).expect("keypair written");
+        path
+    }
+
+    /// Runs the client binary with `args`, the payer as default signer and
+    /// its local state kept next to the ledger.
+    fn client(&self, args: &[&str]) -> Run {
+        self.client_with_program(Some(self.program_id), args)
+    }
+
+    /// As [`Self::client`], with `program_id` instead of the preloaded
+    /// program, or the one recorded for this cluster when `None`.
+    fn client_with_program(&self, program_id: Option<Pubkey>, args: &[&str]) -> Run {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_hello-account-client"));
+        command.args(["--url", &self.url(), "--confirm-timeout", "30"]);
+        if let Some(program_id) = program_id {
+            command.args(["--program-id", &program_id.to_string()]);
+        }
+        let output = command
+            .arg("--keypair-path")
+            .arg(&self.payer)
+            .args(args)
+            .env("HELLO_ACCOUNT_CONFIG_DIR", self.dir.join("config"))
+            .env("RUST_BACKTRACE", "0")
+            .output()
+            .expect("client binary runs");
+        Run { args: args.join(" "), output }
+    }
+
+    /// Airdrops `lamports` to `address` and waits until they arrive.
+    fn fund(&self, address: &Pubkey, lamports: u64) {
+        self.rpc.request_airdrop(address, lamports).expect("airdrop requested");
+        let started = Instant::now();
+        while self.rpc.get_balance(address).expect("balance") < lamports {
+            assert!(started.elapsed() < STARTUP_TIMEOUT, "airdrop to {} not confirmed after {:?}", address, STARTUP_TIMEOUT);
+            thread::sleep(Duration::from_millis(250));
+        }
+    }
+
+    fn greeting(&self, address: Pubkey) -> Option<Greeting> {
+        let account = self.rpc.get_account_with_commitment(&address, CommitmentConfig::confirmed()).ok()?.value?;
+        assert_eq!(account.owner, self.program_id, "{} is not owned by the program", address);
+        Some(Greeting::load(&account.data).expect("account data holds a greeting"))
+    }
+}
+
+impl Drop for TestValidator {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Starts a validator as `start` does, or returns `None` after saying what
+/// is missing, unless `HELLO_ACCOUNT_E2E` requires the suite to run.
+fn start_or_skip(name: &str) -> Option<TestValidator> {
+    match prerequisites() {
+        Err(missing) if env::var_os(REQUIRE_ENV).is_none() => {
+            eprintln!("skipping {}: {} (set {}=1 to fail instead)", name, missing, REQUIRE_ENV);
+            None
+        }
+        _ => Some(TestValidator::start(name)),
+    }
+}
+
+/// Names the first thing the suite needs that this machine lacks.
+fn prerequisites() -> Result<(), String> {
+    let validator = validator_path();
+    if !validator.is_file() {
+        return Err(format!(
+            "{} not found; install the Solana CLI or point SOLANA_TEST_VALIDATOR at the binary",
+            validator.display()
+        ));
+    }
+    let program = sbf_program_path();
+    if !program.is_file() {
+        return Err(format!("{} is missing; run `cargo build-sbf` in hello-account/program", program.display()));
+    }
+    Ok(())
+}
+
+/// `SOLANA_TEST_VALIDATOR`, or `solana-test-validator` looked up on `PATH`.
+fn validator_path() -> PathBuf {
+    if let Some(path) = env::var_os("SOLANA_TEST_VALIDATOR") {
+        return PathBuf::from(path);
+    }
+    let name = "solana-test-validator";
+    env::var_os("PATH")
+        .and_then(|paths| env::split_paths(&paths).map(|dir| dir.join(name)).find(|path| path.is_file()))
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+fn sbf_program_path() -> PathBuf {
+    let dir = env::var("SBF_OUT_DIR").or_else(|_| env::var("BPF_OUT_DIR")).unwrap_or_else(|_| DEFAULT_SBF_OUT_DIR.to_string());
+    Path::new(&dir).join("hello_account.so")
+}
+
+/// One run of the client binary.
+struct Run {
+    args: String,
+    output: Output,
+}
+
+impl Run {
+    fn stdout(&self) -> String {
+        String::from_utf8_lossy(&self.output.stdout).into_owned()
+    }
+
+    fn stderr(&self) -> String {
+        String::from_utf8_lossy(&self.output.stderr).into_owned()
+    }
+
+    #[track_caller]
+    fn assert_success(&self) -> &Self {
+        assert!(
+            self.output.status.success(),
+            "`{}` failed with {}\nstdout:\n{}\nstderr:\n{}",
+            self.args,
+            self.output.status,
+            self.stdout(),
+            self.stderr()
+        );
+        self
+    }
+
+    /// Asserts exit status 1, which `main` returns for every error, with
+    /// `needle` in the error it printed.
+    #[track_caller]
+    fn assert_failure(&self, needle: &str) -> &Self {
+        assert_eq!(self.output.status.code(), Some(1), "`{}` should fail\nstdout:\n{}", self.args, self.stdout());
+        assert!(self.stderr().contains(needle), "`{}` error lacks `{}`:\n{}", self.args, needle, self.stderr());
+        self
+    }
+
+    #[track_caller]
+    fn assert_stdout(&self, needle: &str) -> &Self {
+        assert!(self.stdout().contains(needle), "`{}` output lacks `{}`:\n{}", self.args, needle, self.stdout());
+        self
+    }
+
+    /// The address printed after `label`, e.g. `Greeting Account:`.
+    #[track_caller]
+    fn pubkey_after(&self, label: &str) -> Pubkey {
+        let stdout = self.stdout();
+        let line = stdout.lines().find_map(|line| line.trim().strip_prefix(label));
+        line.and_then(|value| value.trim().parse().ok())
+            .unwrap_or_else(|| panic!("`{}` printed no `{}` address:\n{}", self.args, label, stdout))
+    }
+}
+
+fn program_error(error: HelloError) -> String {
+    format!("custom program error: {:#x}", error as u32)
+}
+
+#[test]
+fn greeting_lifecycle() {
+    let Some(validator) = start_or_skip("lifecycle") else { return };
+    let payer = read_keypair_file(&validator.payer).expect("payer keypair").pubkey();
+
+    let run = validator.client(&["init"]);
+    run.assert_success().assert_stdout("Success!");
+    let greeting = run.pubkey_after("Greeting Account:");
+    let address = greeting.to_string();
+    assert_eq!(validator.greeting(greeting), Some(Greeting { owner: payer, count: 0, message: "Hello, Solana!".to_string() }));
+
+    validator
+        .client(&["get", "--account", &address])
+        .assert_success()
+        .assert_stdout(&format!("Owner: {}", payer))
+        .assert_stdout("Count: 0")
+        .assert_stdout("Message: \"Hello, Solana!\"");
+
+    validator.client(&["update", "--account", &address, "--message", "Hi from e2e"]).assert_success();
+    validator.client(&["increment", "--account", &address]).assert_success();
+    validator
+        .client(&["get", "--account", &address])
+        .assert_success()
+        .assert_stdout("Count: 2")
+        .assert_stdout("Message: \"Hi from e2e\"");
+    assert_eq!(validator.greeting(greeting), Some(Greeting { owner: payer, count: 2, message: "Hi from e2e".to_string() }));
+
+    let balance = validator.rpc.get_balance(&payer).expect("payer balance");
+    validator.client(&["close", "--account", &address]).assert_success().assert_stdout("Account closed");
+    assert_eq!(validator.greeting(greeting), None, "closed greeting still exists");
+    assert!(validator.rpc.get_balance(&payer).expect("payer balance") > balance, "close refunded nothing");
+
+    validator.client(&["get", "--account", &address]).assert_failure("not found");
+}
+
+#[test]
+#[ignore = "needs solana-test-validator and the SBF build: see the module docs"]
+fn failures_exit_nonzero_and_change_nothing() {
+    let validator = TestValidator::start("failures");
+    let run = validator.client(&["init"]);
+    let greeting = run.assert_success().pubkey_after("Greeting Account:");
+    let address = greeting.to_string();
+    let before = validator.greeting(greeting);
+
+    // Rejected by the client before sending
+    let too_long = "x".repeat(MAX_MESSAGE_LEN + 1);
+    validator.client(&["update", "--account", &address, "--message", &too_long]).assert_failure("Message too long");
+
+    // Rejected by the program
+    let intruder = validator.keypair("intruder");
+    let intruder = intruder.to_str().expect("UTF-8 path");
+    validator
+        .client(&["--owner-keypair", intruder, "update", "--account", &address, "--message", "Hacked!"])
+        .assert_failure(&program_error(HelloError::Unauthorized));
+    validator
+        .client(&["--owner-keypair", intruder, "close", "--account", &address])
+        .assert_failure(&program_error(HelloError::Unauthorized));
+
+    validator.client(&["get", "--account", &Pubkey::new_unique().to_string()]).assert_failure("not found");
+    validator.client(&["update", "--account", "no-such-alias", "--message", "Hi"]).assert_failure("neither an address nor a known alias");
+    assert_eq!(validator.greeting(greeting), before, "failed commands changed the greeting");
+}
+
+#[test]
+#[ignore = "needs solana-test-validator and the SBF build: see the module docs"]
+fn deploy_upgrade_and_verify() {
+    let validator = TestValidator::start("deploy");
+    validator.fund(&read_keypair_file(&validator.payer).expect("payer keypair").pubkey(), 100 * LAMPORTS_PER_SOL);
+    let program = sbf_program_path();
+    let program = program.to_str().expect("UTF-8 path");
+    let program_keypair = validator.keypair("program");
+    let program_id = read_keypair_file(&program_keypair).expect("program keypair").pubkey();
+
+    let run = validator.client(&["deploy", "--program", program, "--program-keypair", program_keypair.to_str().expect("UTF-8 path")]);
+    run.assert_success().assert_stdout("Deployed! Signature:").assert_stdout(&format!("Recorded {}", program_id));
+
+    // Later runs without --program-id pick up the deployed program
+    let run = validator.client_with_program(None, &["verify", "--program", program]);
+    run.assert_success().assert_stdout(&format!("Program ID: {}", program_id)).assert_stdout("Match:");
+
+    validator.client_with_program(None, &["upgrade", "--program", program]).assert_success();
+    validator.client_with_program(Some(program_id), &["verify", "--program", program]).assert_success().assert_stdout("Match:");
+
+    let mut modified = fs::read(program).expect("program bytes");
+    modified[100] ^= 0xff;
+    let modified_path = validator.dir.join("modified.so");
+    fs::write(&modified_path, modified).expect("modified program written");
+    validator
+        .client_with_program(Some(program_id), &["verify", "--program", modified_path.to_str().expect("UTF-8 path")])
+        .assert_failure("Mismatch: deployed program differs");
+}
@@ -0,0 +1,182 @@
+//! Calls `Processor::process` with arbitrary accounts and instruction data
+//! and checks what the runtime would enforce on a successful instruction.
+//!
+//! Run with `cargo +nightly fuzz run process` from `hello-account/program`.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use borsh::BorshSerialize;
+use hello_account::{instruction::HelloInstruction, processor::Processor, state::Greeting};
+use hello_account_tests::stubs::{self, Sysvars};
+use libfuzzer_sys::fuzz_target;
+use solana_sdk::{account_info::AccountInfo, pubkey::Pubkey, system_program};
+use std::sync::Once;
+
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([0xAA; 32]);
+
+// A small pool of keys, so that accounts repeat and greeting owners match
+// signers often enough to reach past the authorization checks
+const KEY_POOL: u8 = 4;
+const MAX_ACCOUNTS: usize = 6;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    accounts: Vec<FuzzAccount>,
+    instruction: FuzzInstruction,
+}
+
+#[derive(Arbitrary, Debug)]
+struct FuzzAccount {
+    key: u8,
+    owner: FuzzOwner,
+    is_signer: bool,
+    is_writable: bool,
+    lamports: u64,
+    data: FuzzData,
+}
+
+#[derive(Arbitrary, Debug)]
+enum FuzzOwner {
+    Program,
+    System,
+    Other(u8),
+}
+
+#[derive(Arbitrary, Debug)]
+enum FuzzData {
+    Empty,
+    /// Allocated for a greeting but never written, as `Initialize` expects
+    Zeroed,
+    Greeting { owner: u8, count: u64, message: String },
+    Raw(Vec<u8>),
+}
+
+#[derive(Arbitrary, Debug)]
+enum FuzzInstruction {
+    Initialize { owner: u8 },
+    UpdateMessage { message: String },
+    IncrementOnly,
+    Close,
+    Raw(Vec<u8>),
+}
+
+fn key(index: u8) -> Pubkey {
+    match index % (KEY_POOL + 2) {
+        index if index < KEY_POOL => Pubkey::new_from_array([index + 1; 32]),
+        index if index == KEY_POOL => system_program::id(),
+        _ => PROGRAM_ID,
+    }
+}
+
+impl FuzzData {
+    fn bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Empty => Vec::new(),
+            Self::Zeroed => vec![0; Greeting::space()],
+            Self::Greeting { owner, count, message } => {
+                let mut data = vec![0; Greeting::space()];
+                let greeting = Greeting { owner: key(*owner), count: *count, message: message.clone() };
+                // An oversized message leaves a truncated encoding, which is also worth trying
+                let _ = greeting.serialize(&mut &mut data[..]);
+                data
+            }
+            Self::Raw(bytes) => bytes.clone(),
+        }
+    }
+}
+
+impl FuzzInstruction {
+    fn data(&self) -> Vec<u8> {
+        match self {
+            Self::Initialize { owner } => HelloInstruction::Initialize { owner: key(*owner) }.pack(),
+            Self::UpdateMessage { message } => HelloInstruction::UpdateMessage { message: message.clone() }.pack(),
+            Self::IncrementOnly => HelloInstruction::IncrementOnly.pack(),
+            Self::Close => HelloInstruction::Close.pack(),
+            Self::Raw(bytes) => bytes.clone(),
+        }
+    }
+}
+
+/// One distinct account; repeated keys in the input share it, as the
+/// runtime's duplicate accounts share their lamports and data.
+struct Stored {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+fuzz_target!(|input: Input| {
+    static INSTALL_STUBS: Once = Once::new();
+    INSTALL_STUBS.call_once(|| stubs::install(Sysvars::default()));
+
+    let mut stored: Vec<Stored> = Vec::new();
+    let mut order = Vec::new();
+    for account in input.accounts.iter().take(MAX_ACCOUNTS) {
+        let key = key(account.key);
+        let index = match stored.iter().position(|existing| existing.key == key) {
+            Some(index) => index,
+            None => {
+                let owner = match account.owner {
+                    FuzzOwner::Program => PROGRAM_ID,
+                    FuzzOwner::System => system_program::id(),
+                    FuzzOwner::Other(seed) => Pubkey::new_from_array([seed; 32]),
+                };
+                stored.push(Stored {
+                    key,
+                    owner,
+                    lamports: account.lamports,
+                    data: account.data.bytes(),
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                });
+                stored.len() - 1
+            }
+        };
+        order.push(index);
+    }
+    let before: Vec<(Pubkey, u64, Vec<u8>)> = stored.iter().map(|s| (s.owner, s.lamports, s.data.clone())).collect();
+    let writable: Vec<bool> = stored.iter().map(|s| s.is_writable).collect();
+
+    let infos: Vec<AccountInfo> = stored
+        .iter_mut()
+        .map(|s| AccountInfo::new(&s.key, s.is_signer, s.is_writable, &mut s.lamports, &mut s.data, &s.owner, false, 0))
+        .collect();
+    let accounts: Vec<AccountInfo> = order.iter().map(|&index| infos[index].clone()).collect();
+
+    stubs::take_system_writes();
+    let result = Processor::process(&PROGRAM_ID, &accounts, &input.instruction.data());
+    stubs::take_logs();
+    let system_writes = stubs::take_system_writes();
+
+    // A failed instruction's changes are rolled back
+    if result.is_err() {
+        return;
+    }
+
+    // The processor relies on the runtime here: an instruction that modifies
+    // an account passed read-only fails after it returns, and is rolled back too
+    let modified_read_only = infos.iter().zip(&before).zip(&writable).any(|((info, (owner, lamports, data)), is_writable)| {
+        !is_writable && (*info.owner != *owner || info.lamports() != *lamports || **info.data.borrow() != data[..])
+    });
+    if modified_read_only {
+        return;
+    }
+
+    let lamports_before: u128 = before.iter().map(|(_, lamports, _)| *lamports as u128).sum();
+    let lamports_after: u128 = infos.iter().map(|info| info.lamports() as u128).sum();
+    assert!(lamports_after <= lamports_before, "created {} lamports", lamports_after - lamports_before);
+
+    for (info, (owner, lamports, data)) in infos.iter().zip(&before) {
+        // Only the owner may debit an account or change its data; the system
+        // program's own changes during CPIs are its business
+        if *owner != PROGRAM_ID && !system_writes.contains(info.key) {
+            assert_eq!(info.owner, owner, "reassigned account {} owned by {}", info.key, owner);
+            assert!(info.lamports() >= *lamports, "debited account {} owned by {}", info.key, owner);
+            assert!(**info.data.borrow() == data[..], "wrote to account {} owned by {}", info.key, owner);
+        }
+    }
+});
@@ -27,4 +27,20 @@ impl From<HelloError> for ProgramError {
     fn from(e: HelloError) -> Self {
         ProgramError::Custom(e as u32)
     }
+}
+
+impl TryFrom<u32> for HelloError {
+    type Error = ProgramError;
+
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        Ok(match code {
+            0 => HelloError::InvalidInstruction,
+            1 => HelloError::NotRentExempt,
+            2 => HelloError::ExpectedAmountMismatch,
+            3 => HelloError::AmountOverflow,
+            4 => HelloError::Unauthorized,
+            5 => HelloError::MessageTooLong,
+            _ => return Err(ProgramError::Custom(code)),
+        })
+    }
 }
\ No newline at end of file
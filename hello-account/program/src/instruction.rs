@@ -1,7 +1,7 @@
 use borsh::{BorshSerialize, BorshDeserialize};
 use solana_program::{program_error::ProgramError, pubkey::Pubkey};
 
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
 pub enum HelloInstruction {
     Initialize { owner: Pubkey },
     UpdateMessage { message: String },
@@ -10,6 +10,25 @@ pub enum HelloInstruction {
 }
 
 impl HelloInstruction {
+    /// Encodes the instruction as `unpack` expects it: a variant byte, then
+    /// the raw owner bytes or message bytes, with no length prefix.
+    pub fn pack(&self) -> Vec<u8> {
+        match self {
+            Self::Initialize { owner } => {
+                let mut data = vec![0];
+                data.extend_from_slice(owner.as_ref());
+                data
+            }
+            Self::UpdateMessage { message } => {
+                let mut data = vec![1];
+                data.extend_from_slice(message.as_bytes());
+                data
+            }
+            Self::IncrementOnly => vec![2],
+            Self::Close => vec![3],
+        }
+    }
+
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
         let (&variant, rest) = input
             .split_first()
@@ -1,4 +1,3 @@
-use borsh::{BorshSerialize, BorshDeserialize};
 use solana_program:: {
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
@@ -7,7 +6,7 @@ use solana_program:: {
     program_error::ProgramError,
     pubkey::Pubkey,
     rent::Rent,
-    system_instruction,
+    system_instruction, system_program,
     sysvar::Sysvar,
 };
 
@@ -35,17 +34,17 @@ impl Processor {
 
             HelloInstruction::UpdateMessage {message} => {
                 msg!("Instruction: UpdateMessage");
-                Self::process_update_message(accounts, message)
+                Self::process_update_message(program_id, accounts, message)
             }
 
             HelloInstruction::IncrementOnly => {
                 msg!("Instruction: IncrementOnly");
-                Self::process_increment_only(accounts)
+                Self::process_increment_only(program_id, accounts)
             }
 
             HelloInstruction::Close => {
                 msg!("Instruction: Close");
-                Self::process_close(accounts)
+                Self::process_close(program_id, accounts)
             }
         }
     }
@@ -64,6 +63,10 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
+        if *system_program.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
         let space = Greeting::space();
         let rent = Rent::get()?;
         let lamports = rent.minimum_balance(space);
@@ -85,13 +88,13 @@ impl Processor {
             message: String::from("Hello, Solana!"),
         };
 
-        greeting.serialize(&mut &mut greeting_account.data.borrow_mut()[..])?;
+        greeting.store(&mut greeting_account.data.borrow_mut())?;
 
         msg!("Greeting account created for owner: {}", owner);
         Ok(())
     }
 
-    fn process_update_message(accounts: &[AccountInfo], message: String) -> ProgramResult {
+    fn process_update_message(program_id: &Pubkey, accounts: &[AccountInfo], message: String) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let owner = next_account_info(account_info_iter)?;
         let greeting_account = next_account_info(account_info_iter)?;
@@ -100,7 +103,11 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        let mut greeting = Greeting::try_from_slice(&greeting_account.data.borrow())?;
+        if greeting_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut greeting = Greeting::load(&greeting_account.data.borrow())?;
 
         if greeting.owner != *owner.key {
             return Err(HelloError::Unauthorized.into());
@@ -116,13 +123,13 @@ impl Processor {
             .checked_add(1)
             .ok_or(HelloError::AmountOverflow)?;
 
-        greeting.serialize(&mut &mut greeting_account.data.borrow_mut()[..])?;
+        greeting.store(&mut greeting_account.data.borrow_mut())?;
 
         msg!("Message updated. Count: {}", greeting.count);
         Ok(())
     }
 
-    fn process_increment_only(accounts: &[AccountInfo]) -> ProgramResult {
+    fn process_increment_only(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let owner = next_account_info(account_info_iter)?;
         let greeting_account = next_account_info(account_info_iter)?;
@@ -131,7 +138,11 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        let mut greeting = Greeting::try_from_slice(&greeting_account.data.borrow())?;
+        if greeting_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut greeting = Greeting::load(&greeting_account.data.borrow())?;
 
         if greeting.owner != *owner.key {
             return Err(HelloError::Unauthorized.into());
@@ -142,13 +153,13 @@ impl Processor {
             .checked_add(1)
             .ok_or(HelloError::AmountOverflow)?;
 
-        greeting.serialize(&mut &mut greeting_account.data.borrow_mut()[..])?;
+        greeting.store(&mut greeting_account.data.borrow_mut())?;
 
         msg!("Count incremented to: {}", greeting.count);
         Ok(())
     }
 
-    fn process_close(accounts: &[AccountInfo]) -> ProgramResult {
+    fn process_close(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let owner = next_account_info(account_info_iter)?;
         let greeting_account = next_account_info(account_info_iter)?;
@@ -158,7 +169,11 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        let greeting = Greeting::try_from_slice(&greeting_account.data.borrow())?;
+        if greeting_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let greeting = Greeting::load(&greeting_account.data.borrow())?;
 
         if greeting.owner != *owner.key {
             return Err(HelloError::Unauthorized.into());
@@ -1,9 +1,10 @@
 use borsh::{BorshSerialize, BorshDeserialize};
 use solana_program::pubkey::Pubkey;
+use std::io;
 
 pub const MAX_MESSAGE_LEN: usize = 200;
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Greeting {
     pub owner: Pubkey,
     pub count: u64,
@@ -17,4 +18,26 @@ impl Greeting {
         4 +
         MAX_MESSAGE_LEN
     }
-}
\ No newline at end of file
+
+    /// Decodes the greeting at the start of an account's data. The account
+    /// is sized for the longest message, so the rest must be zero padding.
+    pub fn load(data: &[u8]) -> io::Result<Self> {
+        let mut rest = data;
+        let greeting = Self::deserialize(&mut rest)?;
+        if rest.iter().any(|&byte| byte != 0) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "non-zero bytes after the greeting"));
+        }
+        Ok(greeting)
+    }
+
+    /// Encodes the greeting at the start of `data` and zeroes the rest, so
+    /// that no bytes of a longer previous message are left behind.
+    pub fn store(&self, data: &mut [u8]) -> io::Result<()> {
+        let len = data.len();
+        let mut rest = &mut data[..];
+        self.serialize(&mut rest)?;
+        let written = len - rest.len();
+        data[written..].fill(0);
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+//! Compute-unit budgets compared against a committed JSON baseline.
+//!
+//! After an intentional change, re-bless with `BLESS=1` like the golden
+//! snapshots and commit the updated file.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::golden::bless_requested;
+
+/// Checks the compute units in `measured` against the baseline at `path`, or
+/// rewrites the baseline when blessing. Prints a table of deltas either way
+/// and panics if a case grew by more than `tolerance_percent`, or if the
+/// cases differ from the baseline's.
+#[track_caller]
+pub fn check(path: &Path, measured: &BTreeMap<String, u64>, tolerance_percent: u64) {
+    if bless_requested() {
+        let json = serde_json::to_string_pretty(measured).expect("budgets serialize");
+        fs::write(path, json + "\n").unwrap_or_else(|err| panic!("failed to write {}: {}", path.display(), err));
+        println!("{}", table(measured, measured));
+        return;
+    }
+
+    let contents = fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("failed to read {} ({}); run with BLESS=1 to create it", path.display(), err));
+    let baseline: BTreeMap<String, u64> =
+        serde_json::from_str(&contents).unwrap_or_else(|err| panic!("failed to parse {}: {}", path.display(), err));
+    let table = table(&baseline, measured);
+    println!("{}", table);
+
+    let mut report = String::new();
+    for (name, &expected) in &baseline {
+        match measured.get(name) {
+            None => report.push_str(&format!("\n{}: no longer measured", name)),
+            Some(&actual) if actual > expected + expected * tolerance_percent / 100 => report.push_str(&format!(
+                "\n{}: {} CU over a budget of {} (+{}%)",
+                name,
+                actual,
+                expected,
+                tolerance_percent
+            )),
+            Some(_) => {}
+        }
+    }
+    for name in measured.keys().filter(|name| !baseline.contains_key(*name)) {
+        report.push_str(&format!("\n{}: new case, not in the baseline", name));
+    }
+
+    if !report.is_empty() {
+        panic!(
+            "compute units in {} regressed:{}\n\n{}\nIf the change is intended, re-bless with BLESS=1 cargo test",
+            path.display(),
+            report,
+            table
+        );
+    }
+}
+
+/// One row per case: baseline, measured and the delta between them.
+fn table(baseline: &BTreeMap<String, u64>, measured: &BTreeMap<String, u64>) -> String {
+    let names: Vec<&String> = baseline.keys().chain(measured.keys().filter(|name| !baseline.contains_key(*name))).collect();
+    let width = names.iter().map(|name| name.len()).max().unwrap_or(0).max("case".len());
+
+    let mut out = format!("{:<width$}  {:>8}  {:>8}  {:>8}  {:>7}\n", "case", "baseline", "measured", "delta", "%");
+    for name in names {
+        let cell = |units: Option<&u64>| units.map_or("-".to_string(), u64::to_string);
+        let (before, after) = (baseline.get(name), measured.get(name));
+        let (delta, percent) = match (before, after) {
+            (Some(&before), Some(&after)) => {
+                let delta = after as i64 - before as i64;
+                let percent = if before == 0 { 0.0 } else { delta as f64 * 100.0 / before as f64 };
+                (format!("{:+}", delta), format!("{:+.1}", percent))
+            }
+            _ => ("-".to_string(), "-".to_string()),
+        };
+        out.push_str(&format!(
+            "{:<width$}  {:>8}  {:>8}  {:>8}  {:>7}\n",
+            name,
+            cell(before),
+            cell(after),
+            delta,
+            percent
+        ));
+    }
+    out
+}
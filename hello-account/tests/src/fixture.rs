@@ -0,0 +1,220 @@
+use hello_account::{processor::Processor, state::Greeting};
+use hello_account_client::instructions;
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    bpf_loader,
+    hash::Hash,
+    instruction::Instruction,
+    message::Message,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+use std::{collections::HashSet, env, fs, path::PathBuf};
+
+use crate::{decode_greeting, Outcome, OWNER_FUNDING};
+
+// Where `cargo build-sbf` in `hello-account/program` leaves the program
+const DEFAULT_SBF_OUT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../program/target/deploy");
+
+/// The program's SBF build: `hello_account.so` in `SBF_OUT_DIR` or
+/// `BPF_OUT_DIR` when set, as for `cargo test-sbf`, or else where
+/// `cargo build-sbf` puts it.
+pub fn sbf_program_path() -> PathBuf {
+    let dir = env::var("SBF_OUT_DIR").or_else(|_| env::var("BPF_OUT_DIR")).unwrap_or_else(|_| DEFAULT_SBF_OUT_DIR.to_string());
+    PathBuf::from(dir).join("hello_account.so")
+}
+
+/// A bank running the hello-account program natively, with the context
+/// payer paying every fee.
+pub struct GreetingFixture {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+    // Identical transactions (e.g. two increments) need a fresh blockhash
+    signatures: HashSet<Signature>,
+}
+
+impl GreetingFixture {
+    pub async fn start() -> Self {
+        let program_id = Pubkey::new_unique();
+        let program_test = ProgramTest::new("hello_account", program_id, processor!(Processor::process));
+        Self::start_with(program_test, program_id).await
+    }
+
+    /// A bank running the program's SBF build under the BPF runtime, so that
+    /// compute units are metered as on chain. Panics if it is not built.
+    pub async fn start_sbf() -> Self {
+        let path = sbf_program_path();
+        let data = fs::read(&path).unwrap_or_else(|err| {
+            panic!("failed to read {} ({}); run `cargo build-sbf` in hello-account/program", path.display(), err)
+        });
+
+        let program_id = Pubkey::new_unique();
+        let mut program_test = ProgramTest::default();
+        program_test.add_account(
+            program_id,
+            Account {
+                lamports: Rent::default().minimum_balance(data.len()).max(1),
+                data,
+                owner: bpf_loader::id(),
+                executable: true,
+                rent_epoch: 0,
+            },
+        );
+        Self::start_with(program_test, program_id).await
+    }
+
+    async fn start_with(program_test: ProgramTest, program_id: Pubkey) -> Self {
+        Self {
+            context: program_test.start_with_context().await,
+            program_id,
+            signatures: HashSet::new(),
+        }
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    /// Processes `instructions` with the context payer as fee payer; the
+    /// payer signs in addition to those of `signers` the instructions need.
+    pub async fn process(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Outcome {
+        let outcome = self.process_once(instructions, signers).await;
+        // A fixture that lives across many cases outlasts its blockhash
+        if outcome.result == Err(TransactionError::BlockhashNotFound) {
+            self.context.get_new_latest_blockhash().await.expect("new blockhash");
+            return self.process_once(instructions, signers).await;
+        }
+        outcome
+    }
+
+    async fn process_once(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Outcome {
+        let mut transaction = self.sign(instructions, signers, self.context.last_blockhash);
+        if !self.signatures.insert(transaction.signatures[0]) {
+            let blockhash = self.context.get_new_latest_blockhash().await.expect("new blockhash");
+            transaction = self.sign(instructions, signers, blockhash);
+            self.signatures.insert(transaction.signatures[0]);
+        }
+
+        let processed = self
+            .context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .expect("banks server reachable");
+        let (logs, compute_units) = match processed.metadata {
+            Some(metadata) => (metadata.log_messages, metadata.compute_units_consumed),
+            // Rejected before execution, e.g. for a missing signature
+            None => (Vec::new(), 0),
+        };
+        Outcome { result: processed.result, logs, compute_units }
+    }
+
+    fn sign(&self, instructions: &[Instruction], signers: &[&Keypair], blockhash: Hash) -> Transaction {
+        let payer = &self.context.payer;
+        let message = Message::new(instructions, Some(&payer.pubkey()));
+        let required = &message.account_keys[..message.header.num_required_signatures as usize];
+
+        // Signers the instructions do not ask for are dropped, so that tests
+        // can strip a signature by editing an `AccountMeta`
+        let mut all_signers = vec![payer];
+        all_signers.extend(signers.iter().copied().filter(|signer| {
+            signer.pubkey() != payer.pubkey() && required.contains(&signer.pubkey())
+        }));
+        Transaction::new(&all_signers, message, blockhash)
+    }
+
+    /// A new keypair holding `OWNER_FUNDING` lamports.
+    pub async fn create_owner(&mut self) -> Keypair {
+        let owner = Keypair::new();
+        let transfer = system_instruction::transfer(&self.payer(), &owner.pubkey(), OWNER_FUNDING);
+        self.process(&[transfer], &[]).await.assert_ok();
+        owner
+    }
+
+    /// Initializes `greeting` for `owner`, paid for by the context payer.
+    pub async fn initialize(&mut self, greeting: &Keypair, owner: Pubkey) -> Outcome {
+        let instruction = instructions::initialize(self.program_id, self.payer(), greeting.pubkey(), owner);
+        self.process(&[instruction], &[greeting]).await
+    }
+
+    /// Initializes a new greeting for `owner` and returns its address.
+    pub async fn create_greeting(&mut self, owner: Pubkey) -> Pubkey {
+        let greeting = Keypair::new();
+        self.initialize(&greeting, owner).await.assert_ok();
+        greeting.pubkey()
+    }
+
+    pub async fn update(&mut self, owner: &Keypair, greeting: Pubkey, message: &str) -> Outcome {
+        let instruction = instructions::update_message(self.program_id, owner.pubkey(), greeting, message);
+        self.process(&[instruction], &[owner]).await
+    }
+
+    pub async fn increment(&mut self, owner: &Keypair, greeting: Pubkey) -> Outcome {
+        let instruction = instructions::increment_only(self.program_id, owner.pubkey(), greeting);
+        self.process(&[instruction], &[owner]).await
+    }
+
+    pub async fn close(&mut self, owner: &Keypair, greeting: Pubkey, destination: Pubkey) -> Outcome {
+        let instruction = instructions::close(self.program_id, owner.pubkey(), greeting, destination);
+        self.process(&[instruction], &[owner]).await
+    }
+
+    /// Stores `account` at `address` directly, bypassing every check.
+    pub fn set_account(&mut self, address: Pubkey, account: Account) {
+        self.context.set_account(&address, &AccountSharedData::from(account));
+    }
+
+    /// Stores a rent-exempt greeting account laid out as `Initialize` would,
+    /// but owned by `program_owner`, which need not be this program.
+    pub async fn set_greeting(&mut self, address: Pubkey, program_owner: Pubkey, greeting: &Greeting) {
+        let mut data = vec![0; Greeting::space()];
+        greeting.store(&mut data).expect("greeting fits the account");
+        let rent = self.context.banks_client.get_rent().await.expect("banks server reachable");
+        self.set_account(
+            address,
+            Account {
+                lamports: rent.minimum_balance(data.len()),
+                data,
+                owner: program_owner,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    pub async fn account(&mut self, address: Pubkey) -> Option<Account> {
+        self.context.banks_client.get_account(address).await.expect("banks server reachable")
+    }
+
+    pub async fn lamports(&mut self, address: Pubkey) -> u64 {
+        self.account(address).await.map_or(0, |account| account.lamports)
+    }
+
+    /// Decodes the greeting stored at `address`, which must exist.
+    pub async fn greeting(&mut self, address: Pubkey) -> Greeting {
+        let account = self
+            .account(address)
+            .await
+            .unwrap_or_else(|| panic!("greeting account {} does not exist", address));
+        assert_eq!(account.owner, self.program_id, "greeting account {} is not owned by the program", address);
+        decode_greeting(&account.data)
+    }
+
+    pub async fn assert_greeting(&mut self, address: Pubkey, owner: Pubkey, count: u64, message: &str) {
+        let greeting = self.greeting(address).await;
+        assert_eq!(greeting.owner, owner, "owner of {}", address);
+        assert_eq!(greeting.count, count, "count of {}", address);
+        assert_eq!(greeting.message, message, "message of {}", address);
+    }
+
+    /// Asserts that `address` holds no lamports, so the runtime has purged it.
+    pub async fn assert_closed(&mut self, address: Pubkey) {
+        if let Some(account) = self.account(address).await {
+            panic!("{} still exists with {} lamports", address, account.lamports);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Byte snapshots compared against a committed JSON file of hex strings.
+//!
+//! After an intentional change, re-bless with `BLESS=1 cargo test` in
+//! `hello-account/tests` and commit the updated file.
+
+use std::{collections::BTreeMap, env, fs, path::Path};
+
+const BLESS_ENV: &str = "BLESS";
+const ROW_LEN: usize = 16;
+
+/// Whether snapshots should be rewritten instead of checked.
+pub fn bless_requested() -> bool {
+    env::var(BLESS_ENV).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Checks `actual` against the snapshot file at `path`, or rewrites the file
+/// when blessing. Panics with a hex diff of every entry that changed.
+#[track_caller]
+pub fn check(path: &Path, actual: &BTreeMap<String, Vec<u8>>) {
+    let actual_hex: BTreeMap<&str, String> = actual.iter().map(|(name, bytes)| (name.as_str(), to_hex(bytes))).collect();
+
+    if bless_requested() {
+        let json = serde_json::to_string_pretty(&actual_hex).expect("snapshots serialize");
+        fs::write(path, json + "\n").unwrap_or_else(|err| panic!("failed to write {}: {}", path.display(), err));
+        return;
+    }
+
+    let contents = fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("failed to read {} ({}); run with {}=1 to create it", path.display(), err, BLESS_ENV));
+    let expected: BTreeMap<String, String> =
+        serde_json::from_str(&contents).unwrap_or_else(|err| panic!("failed to parse {}: {}", path.display(), err));
+
+    let mut report = String::new();
+    for (name, hex) in &expected {
+        match actual.get(name) {
+            None => report.push_str(&format!("\n{}: snapshot no longer produced\n", name)),
+            Some(bytes) if to_hex(bytes) != *hex => {
+                let expected_bytes = from_hex(hex).unwrap_or_else(|| panic!("{} in {} is not hex", name, path.display()));
+                report.push_str(&format!("\n{}:\n{}", name, diff(&expected_bytes, bytes)));
+            }
+            Some(_) => {}
+        }
+    }
+    for name in actual.keys().filter(|name| !expected.contains_key(*name)) {
+        report.push_str(&format!("\n{}: new snapshot, not in the file\n", name));
+    }
+
+    if !report.is_empty() {
+        panic!(
+            "snapshots in {} changed (- expected, + actual):\n{}\nIf the change is intended, re-bless with {}=1 cargo test",
+            path.display(),
+            report,
+            BLESS_ENV
+        );
+    }
+}
+
+/// Hex dump rows that differ, with their offsets.
+fn diff(expected: &[u8], actual: &[u8]) -> String {
+    let mut out = String::new();
+    if expected.len() != actual.len() {
+        out.push_str(&format!("  length {} -> {}\n", expected.len(), actual.len()));
+    }
+    let rows = expected.len().max(actual.len()).div_ceil(ROW_LEN);
+    for row in 0..rows {
+        let range = |bytes: &[u8]| {
+            let start = (row * ROW_LEN).min(bytes.len());
+            let end = ((row + 1) * ROW_LEN).min(bytes.len());
+            bytes[start..end].to_vec()
+        };
+        let (before, after) = (range(expected), range(actual));
+        if before != after {
+            out.push_str(&format!("- {:04x}: {}\n", row * ROW_LEN, spaced_hex(&before)));
+            out.push_str(&format!("+ {:04x}: {}\n", row * ROW_LEN, spaced_hex(&after)));
+        }
+    }
+    out
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn spaced_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<_>>().join(" ")
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
@@ -0,0 +1,331 @@
+use hello_account::{processor::Processor, state::Greeting};
+use hello_account_client::instructions;
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    program::invoke,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::TransactionError,
+};
+use std::{collections::HashMap, sync::Once};
+
+use crate::{
+    decode_greeting,
+    stubs::{self, Sysvars},
+    Outcome, OWNER_FUNDING,
+};
+
+/// Entrypoint of a native program, as `Processor::process`.
+pub type ProcessInstruction = fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult;
+
+const PAYER_FUNDING: u64 = 1_000 * LAMPORTS_PER_SOL;
+
+/// Runs the hello-account program synchronously against an in-memory account
+/// store, through the syscall stubs, and enforces after every instruction
+/// what the runtime would: read-only accounts stay unchanged, only an
+/// account's owner debits it or changes its data, lamports are conserved and
+/// no account becomes rent-paying.
+///
+/// Signatures are not checked: an `AccountMeta` marked as signer counts as
+/// signed. Compute units are not metered and report as 0. The stubs are
+/// process-wide, so tests using a harness cannot share a binary with
+/// `ProgramTest`.
+pub struct Harness {
+    pub program_id: Pubkey,
+    pub payer: Keypair,
+    process_instruction: ProcessInstruction,
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        Self::with_processor(Processor::process)
+    }
+
+    /// A harness running `process_instruction` in place of the program.
+    pub fn with_processor(process_instruction: ProcessInstruction) -> Self {
+        static INSTALL_STUBS: Once = Once::new();
+        INSTALL_STUBS.call_once(|| stubs::install(Sysvars::default()));
+
+        let mut harness = Self {
+            program_id: Pubkey::new_unique(),
+            payer: Keypair::new(),
+            process_instruction,
+            accounts: HashMap::new(),
+        };
+        harness.set_lamports(harness.payer.pubkey(), PAYER_FUNDING);
+        harness
+    }
+
+    /// The rent the program sees and the rent-exemption check applies.
+    pub fn rent(&self) -> Rent {
+        Sysvars::default().rent
+    }
+
+    /// Processes `instructions` as one transaction: if any fails, none of
+    /// their changes are kept. Instructions to the system program run
+    /// through the stubbed system program.
+    pub fn process(&mut self, instructions: &[Instruction]) -> Outcome {
+        stubs::take_logs();
+        let result = self.execute(instructions);
+        Outcome { result, logs: stubs::take_logs(), compute_units: 0 }
+    }
+
+    fn execute(&mut self, instructions: &[Instruction]) -> Result<(), TransactionError> {
+        // Signer and writable flags are per transaction, as in a message
+        let mut keys: Vec<Pubkey> = Vec::new();
+        let mut is_signer: Vec<bool> = Vec::new();
+        let mut is_writable: Vec<bool> = Vec::new();
+        for meta in instructions.iter().flat_map(|instruction| &instruction.accounts) {
+            match keys.iter().position(|key| *key == meta.pubkey) {
+                Some(index) => {
+                    is_signer[index] |= meta.is_signer;
+                    is_writable[index] |= meta.is_writable;
+                }
+                None => {
+                    keys.push(meta.pubkey);
+                    is_signer.push(meta.is_signer);
+                    is_writable.push(meta.is_writable);
+                }
+            }
+        }
+
+        let mut accounts: Vec<Account> = keys.iter().map(|key| self.account(*key).unwrap_or_default()).collect();
+        let mut allocated = vec![false; keys.len()];
+        for (index, account) in accounts.iter_mut().enumerate() {
+            // Native `AccountInfo`s cannot grow, so any account the program
+            // might create is allocated up front at the only size it creates
+            let creatable = account.lamports == 0 && account.owner == system_program::id() && account.data.is_empty();
+            if creatable && is_signer[index] && is_writable[index] {
+                account.data = vec![0; Greeting::space()];
+                allocated[index] = true;
+            }
+        }
+
+        for (index, instruction) in instructions.iter().enumerate() {
+            let used: Vec<usize> = instruction
+                .accounts
+                .iter()
+                .map(|meta| keys.iter().position(|key| *key == meta.pubkey).expect("collected above"))
+                .collect();
+            self.execute_instruction(instruction, &keys, &mut accounts, &used, &is_signer, &is_writable)
+                .map_err(|error| TransactionError::InstructionError(index as u8, error))?;
+        }
+
+        for (index, account) in accounts.iter_mut().enumerate() {
+            if allocated[index] && account.owner == system_program::id() {
+                account.data.clear();
+            }
+        }
+
+        // As the runtime does, reject accounts that newly fall below rent exemption
+        let rent = self.rent();
+        for (index, (key, account)) in keys.iter().zip(&accounts).enumerate() {
+            let was_exempt = self.account(*key).is_none_or(|pre| rent.is_exempt(pre.lamports, pre.data.len()));
+            if account.lamports > 0 && !rent.is_exempt(account.lamports, account.data.len()) && was_exempt {
+                return Err(TransactionError::InsufficientFundsForRent { account_index: index as u8 });
+            }
+        }
+
+        for (key, account) in keys.into_iter().zip(accounts) {
+            self.set_account(key, account);
+        }
+        Ok(())
+    }
+
+    fn execute_instruction(
+        &self,
+        instruction: &Instruction,
+        keys: &[Pubkey],
+        accounts: &mut [Account],
+        used: &[usize],
+        is_signer: &[bool],
+        is_writable: &[bool],
+    ) -> Result<(), InstructionError> {
+        let before: Vec<(usize, Account)> = accounts
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| used.contains(index))
+            .map(|(index, account)| (index, account.clone()))
+            .collect();
+
+        let infos: Vec<(usize, AccountInfo)> = accounts
+            .iter_mut()
+            .enumerate()
+            .filter(|(index, _)| used.contains(index))
+            .map(|(index, account)| {
+                let info = AccountInfo::new(
+                    &keys[index],
+                    is_signer[index],
+                    is_writable[index],
+                    &mut account.lamports,
+                    &mut account.data,
+                    &account.owner,
+                    account.executable,
+                    account.rent_epoch,
+                );
+                (index, info)
+            })
+            .collect();
+        // Duplicate metas share one `AccountInfo`, as they share one account
+        let account_infos: Vec<AccountInfo> = used
+            .iter()
+            .map(|index| infos.iter().find(|(i, _)| i == index).expect("built above").1.clone())
+            .collect();
+
+        stubs::take_system_writes();
+        let result = if instruction.program_id == system_program::id() {
+            invoke(instruction, &account_infos)
+        } else if instruction.program_id == self.program_id {
+            (self.process_instruction)(&self.program_id, &account_infos, &instruction.data)
+        } else {
+            return Err(InstructionError::UnsupportedProgramId);
+        };
+        let system_writes = stubs::take_system_writes();
+        let after: Vec<(usize, Pubkey, u64, Vec<u8>)> = infos
+            .iter()
+            .map(|(index, info)| (*index, *info.owner, info.lamports(), info.data.borrow().to_vec()))
+            .collect();
+        drop(account_infos);
+        drop(infos);
+        result.map_err(|error| InstructionError::from(u64::from(error)))?;
+
+        let program_id = instruction.program_id;
+        for ((index, pre), (_, owner, lamports, data)) in before.iter().zip(&after) {
+            let modified = |pre: &Account| (pre.owner != *owner, pre.lamports != *lamports, pre.data != *data);
+            let (owner_changed, lamports_changed, data_changed) = modified(pre);
+            if !is_writable[*index] {
+                if lamports_changed {
+                    return Err(InstructionError::ReadonlyLamportChange);
+                }
+                if data_changed {
+                    return Err(InstructionError::ReadonlyDataModified);
+                }
+            }
+            // The system program's own changes during CPIs are its business
+            if pre.owner != program_id && program_id != system_program::id() && !system_writes.contains(&keys[*index]) {
+                if owner_changed {
+                    return Err(InstructionError::ModifiedProgramId);
+                }
+                if *lamports < pre.lamports {
+                    return Err(InstructionError::ExternalAccountLamportSpend);
+                }
+                if data_changed {
+                    return Err(InstructionError::ExternalAccountDataModified);
+                }
+            }
+        }
+        let lamports_before: u128 = before.iter().map(|(_, pre)| pre.lamports as u128).sum();
+        let lamports_after: u128 = after.iter().map(|(_, _, lamports, _)| *lamports as u128).sum();
+        if lamports_before != lamports_after {
+            return Err(InstructionError::UnbalancedInstruction);
+        }
+
+        for (index, owner, _, _) in after {
+            accounts[index].owner = owner;
+        }
+        Ok(())
+    }
+
+    /// A new keypair holding `OWNER_FUNDING` lamports.
+    pub fn create_owner(&mut self) -> Keypair {
+        let owner = Keypair::new();
+        self.set_lamports(owner.pubkey(), OWNER_FUNDING);
+        owner
+    }
+
+    /// Initializes `greeting` for `owner`, paid for by the harness payer.
+    pub fn initialize(&mut self, greeting: &Keypair, owner: Pubkey) -> Outcome {
+        let instruction = instructions::initialize(self.program_id, self.payer.pubkey(), greeting.pubkey(), owner);
+        self.process(&[instruction])
+    }
+
+    /// Initializes a new greeting for `owner` and returns its address.
+    pub fn create_greeting(&mut self, owner: Pubkey) -> Pubkey {
+        let greeting = Keypair::new();
+        self.initialize(&greeting, owner).assert_ok();
+        greeting.pubkey()
+    }
+
+    pub fn update(&mut self, owner: &Keypair, greeting: Pubkey, message: &str) -> Outcome {
+        self.process(&[instructions::update_message(self.program_id, owner.pubkey(), greeting, message)])
+    }
+
+    pub fn increment(&mut self, owner: &Keypair, greeting: Pubkey) -> Outcome {
+        self.process(&[instructions::increment_only(self.program_id, owner.pubkey(), greeting)])
+    }
+
+    pub fn close(&mut self, owner: &Keypair, greeting: Pubkey, destination: Pubkey) -> Outcome {
+        self.process(&[instructions::close(self.program_id, owner.pubkey(), greeting, destination)])
+    }
+
+    /// Stores `account` at `address` directly, bypassing every check. An
+    /// account without lamports is removed, as the runtime purges it.
+    pub fn set_account(&mut self, address: Pubkey, account: Account) {
+        if account.lamports == 0 {
+            self.accounts.remove(&address);
+        } else {
+            self.accounts.insert(address, account);
+        }
+    }
+
+    /// Stores a system account holding `lamports`.
+    pub fn set_lamports(&mut self, address: Pubkey, lamports: u64) {
+        self.set_account(address, Account { lamports, ..Account::default() });
+    }
+
+    /// Stores a rent-exempt greeting account laid out as `Initialize` would,
+    /// but owned by `program_owner`, which need not be this program.
+    pub fn set_greeting(&mut self, address: Pubkey, program_owner: Pubkey, greeting: &Greeting) {
+        let mut data = vec![0; Greeting::space()];
+        greeting.store(&mut data).expect("greeting fits the account");
+        let lamports = self.rent().minimum_balance(data.len());
+        self.set_account(address, Account { lamports, data, owner: program_owner, executable: false, rent_epoch: 0 });
+    }
+
+    pub fn account(&self, address: Pubkey) -> Option<Account> {
+        self.accounts.get(&address).cloned()
+    }
+
+    pub fn lamports(&self, address: Pubkey) -> u64 {
+        self.accounts.get(&address).map_or(0, |account| account.lamports)
+    }
+
+    /// Decodes the greeting stored at `address`, which must exist.
+    pub fn greeting(&self, address: Pubkey) -> Greeting {
+        let account = self
+            .accounts
+            .get(&address)
+            .unwrap_or_else(|| panic!("greeting account {} does not exist", address));
+        assert_eq!(account.owner, self.program_id, "greeting account {} is not owned by the program", address);
+        decode_greeting(&account.data)
+    }
+
+    #[track_caller]
+    pub fn assert_greeting(&self, address: Pubkey, owner: Pubkey, count: u64, message: &str) {
+        let greeting = self.greeting(address);
+        assert_eq!(greeting.owner, owner, "owner of {}", address);
+        assert_eq!(greeting.count, count, "count of {}", address);
+        assert_eq!(greeting.message, message, "message of {}", address);
+    }
+
+    /// Asserts that `address` holds no lamports, so it has been purged.
+    #[track_caller]
+    pub fn assert_closed(&self, address: Pubkey) {
+        if let Some(account) = self.accounts.get(&address) {
+            panic!("{} still exists with {} lamports", address, account.lamports);
+        }
+    }
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
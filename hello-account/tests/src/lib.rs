@@ -1,14 +1,36 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+//! Test support for the hello-account program: a `ProgramTest` fixture and a
+//! synchronous in-process harness with high-level actions, assertions on
+//! greeting state, errors and logs, proptest generators, syscall stubs for
+//! calling the processor directly, golden byte snapshots, compute-unit
+//! budgets and a reference model of the greeting lifecycle.
+
+pub mod budget;
+#[cfg(feature = "program-test")]
+mod fixture;
+pub mod golden;
+#[cfg(feature = "client")]
+mod harness;
+pub mod model;
+mod outcome;
+pub mod strategies;
+pub mod stubs;
+
+use hello_account::state::Greeting;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+#[cfg(feature = "program-test")]
+pub use fixture::{sbf_program_path, GreetingFixture};
+#[cfg(feature = "client")]
+pub use harness::{Harness, ProcessInstruction};
+pub use outcome::Outcome;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
\ No newline at end of file
+/// Lamports `create_owner` gives each owner, enough to pay for greetings.
+pub const OWNER_FUNDING: u64 = LAMPORTS_PER_SOL;
+
+/// Message every greeting starts with after `Initialize`.
+pub const INITIAL_MESSAGE: &str = "Hello, Solana!";
+
+/// Decodes greeting account data as the program does.
+pub fn decode_greeting(data: &[u8]) -> Greeting {
+    Greeting::load(data).expect("account data holds a greeting")
+}
@@ -0,0 +1,113 @@
+//! A pure-Rust reference for the greeting lifecycle: what every operation
+//! should do to a fixed set of greeting accounts and actors, and the exact
+//! error the program should reject it with.
+//!
+//! Actors and greeting accounts are referred to by index, so that operations
+//! can be generated and shrunk without keys.
+
+use hello_account::{error::HelloError, state::MAX_MESSAGE_LEN};
+use solana_sdk::instruction::InstructionError;
+
+use crate::INITIAL_MESSAGE;
+
+/// Owners that sign operations and receive refunds.
+pub const ACTORS: usize = 3;
+/// Greeting accounts operations are aimed at.
+pub const SLOTS: usize = 3;
+
+// `SystemError::AccountAlreadyInUse`, from the `create_account` CPI
+const ACCOUNT_ALREADY_IN_USE: u32 = 0;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// The context payer initializes `slot` for `owner`
+    Initialize { slot: usize, owner: usize },
+    UpdateMessage { actor: usize, slot: usize, message: String },
+    IncrementOnly { actor: usize, slot: usize },
+    Close { actor: usize, slot: usize, destination: usize },
+    /// Stores a greeting in `slot` directly, e.g. with a count near
+    /// `u64::MAX` that increments could never reach
+    Preset { slot: usize, owner: usize, count: u64, message: String },
+}
+
+/// A greeting as the model keeps it, with its owner as an actor index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModelGreeting {
+    pub owner: usize,
+    pub count: u64,
+    pub message: String,
+    pub lamports: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Model {
+    /// Lamports every greeting account holds: the rent-exempt minimum
+    pub greeting_lamports: u64,
+    pub greetings: [Option<ModelGreeting>; SLOTS],
+    /// Lamports each actor received from closed greetings
+    pub refunds: [u64; ACTORS],
+}
+
+impl Model {
+    pub fn new(greeting_lamports: u64) -> Self {
+        Self { greeting_lamports, greetings: Default::default(), refunds: [0; ACTORS] }
+    }
+
+    /// Applies `operation` if the program should accept it, or returns the
+    /// error it should fail with and leaves the model unchanged.
+    pub fn apply(&mut self, operation: &Operation) -> Result<(), InstructionError> {
+        match operation {
+            Operation::Initialize { slot, owner } => {
+                if self.greetings[*slot].is_some() {
+                    return Err(InstructionError::Custom(ACCOUNT_ALREADY_IN_USE));
+                }
+                self.greetings[*slot] = Some(ModelGreeting {
+                    owner: *owner,
+                    count: 0,
+                    message: INITIAL_MESSAGE.to_string(),
+                    lamports: self.greeting_lamports,
+                });
+            }
+            Operation::UpdateMessage { actor, slot, message } => {
+                let greeting = self.authorize(*actor, *slot)?;
+                if message.len() > MAX_MESSAGE_LEN {
+                    return Err(hello_error(HelloError::MessageTooLong));
+                }
+                greeting.count = greeting.count.checked_add(1).ok_or(hello_error(HelloError::AmountOverflow))?;
+                greeting.message = message.clone();
+            }
+            Operation::IncrementOnly { actor, slot } => {
+                let greeting = self.authorize(*actor, *slot)?;
+                greeting.count = greeting.count.checked_add(1).ok_or(hello_error(HelloError::AmountOverflow))?;
+            }
+            Operation::Close { actor, slot, destination } => {
+                let lamports = self.authorize(*actor, *slot)?.lamports;
+                self.refunds[*destination] += lamports;
+                self.greetings[*slot] = None;
+            }
+            Operation::Preset { slot, owner, count, message } => {
+                self.greetings[*slot] = Some(ModelGreeting {
+                    owner: *owner,
+                    count: *count,
+                    message: message.clone(),
+                    lamports: self.greeting_lamports,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The greeting in `slot`, if `actor` may change it.
+    fn authorize(&mut self, actor: usize, slot: usize) -> Result<&mut ModelGreeting, InstructionError> {
+        // A closed or never initialized account belongs to the system program
+        let greeting = self.greetings[slot].as_mut().ok_or(InstructionError::IncorrectProgramId)?;
+        if greeting.owner != actor {
+            return Err(hello_error(HelloError::Unauthorized));
+        }
+        Ok(greeting)
+    }
+}
+
+fn hello_error(error: HelloError) -> InstructionError {
+    InstructionError::Custom(error as u32)
+}
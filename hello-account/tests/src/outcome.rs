@@ -0,0 +1,94 @@
+use hello_account::error::HelloError;
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+
+/// Result of one processed transaction, with the logs and compute units it
+/// produced.
+#[derive(Debug)]
+pub struct Outcome {
+    pub result: Result<(), TransactionError>,
+    pub logs: Vec<String>,
+    pub compute_units: u64,
+}
+
+impl Outcome {
+    /// The instruction error the transaction failed with, if any.
+    pub fn instruction_error(&self) -> Option<&InstructionError> {
+        match &self.result {
+            Err(TransactionError::InstructionError(_, error)) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// The program error the transaction failed with, if it was a `HelloError`.
+    pub fn hello_error(&self) -> Option<HelloError> {
+        match self.instruction_error() {
+            Some(InstructionError::Custom(code)) => HelloError::try_from(*code).ok(),
+            _ => None,
+        }
+    }
+
+    #[track_caller]
+    pub fn assert_ok(&self) -> &Self {
+        if let Err(error) = &self.result {
+            panic!("transaction failed: {}\n{}", error, self.format_logs());
+        }
+        self
+    }
+
+    #[track_caller]
+    pub fn assert_hello_error(&self, expected: HelloError) -> &Self {
+        match self.hello_error() {
+            Some(error) if error as u32 == expected as u32 => self,
+            _ => panic!(
+                "expected HelloError::{:?}, got {}\n{}",
+                expected,
+                self.describe_result(),
+                self.format_logs()
+            ),
+        }
+    }
+
+    /// Asserts a non-custom failure such as `MissingRequiredSignature`.
+    #[track_caller]
+    pub fn assert_instruction_error(&self, expected: InstructionError) -> &Self {
+        if self.instruction_error() != Some(&expected) {
+            panic!("expected {:?}, got {}\n{}", expected, self.describe_result(), self.format_logs());
+        }
+        self
+    }
+
+    /// Asserts that the transaction failed, with an error that is not one of
+    /// the program's instruction errors (e.g. a runtime or signature check).
+    #[track_caller]
+    pub fn assert_transaction_error(&self, expected: TransactionError) -> &Self {
+        if self.result.as_ref().err() != Some(&expected) {
+            panic!("expected {:?}, got {}\n{}", expected, self.describe_result(), self.format_logs());
+        }
+        self
+    }
+
+    #[track_caller]
+    pub fn assert_log(&self, needle: &str) -> &Self {
+        if !self.logs.iter().any(|line| line.contains(needle)) {
+            panic!("no log line contains `{}`\n{}", needle, self.format_logs());
+        }
+        self
+    }
+
+    fn describe_result(&self) -> String {
+        match (&self.result, self.hello_error()) {
+            (Ok(()), _) => "success".to_string(),
+            (Err(_), Some(error)) => format!("HelloError::{:?}", error),
+            (Err(error), None) => format!("{:?}", error),
+        }
+    }
+
+    fn format_logs(&self) -> String {
+        let mut formatted = String::from("logs:");
+        for line in &self.logs {
+            formatted.push_str("\n  ");
+            formatted.push_str(line);
+        }
+        formatted
+    }
+}
@@ -0,0 +1,66 @@
+//! Proptest generators for program inputs and state.
+
+use hello_account::{
+    instruction::HelloInstruction,
+    state::{Greeting, MAX_MESSAGE_LEN},
+};
+use proptest::{collection::vec, prelude::*};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::model::{Operation, ACTORS, SLOTS};
+
+pub fn pubkey() -> impl Strategy<Value = Pubkey> {
+    any::<[u8; 32]>().prop_map(Pubkey::new_from_array)
+}
+
+/// Any string of at most `max_len` bytes, multi-byte characters included.
+pub fn message(max_len: usize) -> impl Strategy<Value = String> {
+    vec(any::<char>(), 0..=max_len).prop_map(move |chars| {
+        let mut message = String::new();
+        for c in chars {
+            if message.len() + c.len_utf8() > max_len {
+                break;
+            }
+            message.push(c);
+        }
+        message
+    })
+}
+
+/// A string longer than `MAX_MESSAGE_LEN` bytes.
+pub fn oversized_message() -> impl Strategy<Value = String> {
+    vec(any::<char>(), MAX_MESSAGE_LEN + 1..MAX_MESSAGE_LEN * 2).prop_map(String::from_iter)
+}
+
+pub fn instruction() -> impl Strategy<Value = HelloInstruction> {
+    prop_oneof![
+        pubkey().prop_map(|owner| HelloInstruction::Initialize { owner }),
+        message(MAX_MESSAGE_LEN * 2).prop_map(|message| HelloInstruction::UpdateMessage { message }),
+        Just(HelloInstruction::IncrementOnly),
+        Just(HelloInstruction::Close),
+    ]
+}
+
+/// A greeting the program can store, with a message of at most
+/// `MAX_MESSAGE_LEN` bytes.
+pub fn greeting() -> impl Strategy<Value = Greeting> {
+    (pubkey(), any::<u64>(), message(MAX_MESSAGE_LEN)).prop_map(|(owner, count, message)| Greeting { owner, count, message })
+}
+
+/// An operation of the reference model, aimed at one of its actors and
+/// greeting accounts. Messages are sometimes too long and preset counts are
+/// often about to overflow.
+pub fn operation() -> impl Strategy<Value = Operation> {
+    let actor = || 0..ACTORS;
+    let slot = || 0..SLOTS;
+    let new_message = || prop_oneof![4 => message(MAX_MESSAGE_LEN), 1 => oversized_message()];
+    let count = prop_oneof![any::<u64>(), (u64::MAX - 2..=u64::MAX)];
+    prop_oneof![
+        2 => (slot(), actor()).prop_map(|(slot, owner)| Operation::Initialize { slot, owner }),
+        3 => (actor(), slot(), new_message()).prop_map(|(actor, slot, message)| Operation::UpdateMessage { actor, slot, message }),
+        3 => (actor(), slot()).prop_map(|(actor, slot)| Operation::IncrementOnly { actor, slot }),
+        1 => (actor(), slot(), actor()).prop_map(|(actor, slot, destination)| Operation::Close { actor, slot, destination }),
+        1 => (slot(), actor(), count, message(MAX_MESSAGE_LEN))
+            .prop_map(|(slot, owner, count, message)| Operation::Preset { slot, owner, count, message }),
+    ]
+}
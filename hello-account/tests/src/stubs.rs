@@ -0,0 +1,169 @@
+//! Syscall stubs for calling `Processor::process` directly, without a bank:
+//! sysvars come from fixed values, logs are captured per thread and CPIs to
+//! the system program are executed against the caller's `AccountInfo`s.
+//!
+//! `ProgramTest` installs its own stubs, so tests using these belong in a
+//! separate test binary.
+
+use solana_sdk::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::{SystemError, SystemInstruction},
+    system_program,
+};
+use std::cell::RefCell;
+
+thread_local! {
+    static LOGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    static SYSTEM_WRITES: RefCell<Vec<Pubkey>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Sysvar values returned to the program.
+#[derive(Clone, Debug, Default)]
+pub struct Sysvars {
+    pub rent: Rent,
+    pub clock: Clock,
+}
+
+/// Replaces the process-wide syscall stubs.
+pub fn install(sysvars: Sysvars) {
+    set_syscall_stubs(Box::new(Stubs { sysvars }));
+}
+
+/// Log lines this thread's program invocations produced since the last call.
+pub fn take_logs() -> Vec<String> {
+    LOGS.with(|logs| logs.take())
+}
+
+/// Accounts this thread's system program CPIs debited or assigned since the
+/// last call; those changes are the system program's, not the caller's.
+pub fn take_system_writes() -> Vec<Pubkey> {
+    SYSTEM_WRITES.with(|writes| writes.take())
+}
+
+struct Stubs {
+    sysvars: Sysvars,
+}
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, message: &str) {
+        LOGS.with(|logs| logs.borrow_mut().push(format!("Program log: {}", message)));
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        // SAFETY: `Rent::get` passes a pointer to a `Rent`
+        unsafe { *(var_addr as *mut Rent) = self.sysvars.rent };
+        SUCCESS
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        // SAFETY: `Clock::get` passes a pointer to a `Clock`
+        unsafe { *(var_addr as *mut Clock) = self.sysvars.clock.clone() };
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if instruction.program_id != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        // No program in this repo signs for PDAs
+        if !signers_seeds.is_empty() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // As the runtime does, refuse to escalate privileges across the CPI
+        let mut accounts = Vec::with_capacity(instruction.accounts.len());
+        for meta in &instruction.accounts {
+            let info = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if (meta.is_signer && !info.is_signer) || (meta.is_writable && !info.is_writable) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            accounts.push(info);
+        }
+
+        let system_instruction = limited_deserialize(&instruction.data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        match system_instruction {
+            SystemInstruction::CreateAccount { lamports, space, owner } => {
+                let [from, to, ..] = accounts[..] else {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                };
+                if !to.is_signer {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                if to.lamports() != 0 || *to.owner != system_program::id() {
+                    return Err(system_error(SystemError::AccountAlreadyInUse));
+                }
+                // Native `AccountInfo`s cannot grow, so accounts to be
+                // created must already be allocated at their final size
+                if to.data_len() as u64 != space || to.data.borrow().iter().any(|&byte| byte != 0) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                transfer(from, to, lamports)?;
+                to.assign(&owner);
+                record_system_write(to);
+            }
+            SystemInstruction::Transfer { lamports } => {
+                let [from, to, ..] = accounts[..] else {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                };
+                transfer(from, to, lamports)?;
+            }
+            SystemInstruction::Assign { owner } => {
+                let [account, ..] = accounts[..] else {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                };
+                if !account.is_signer || *account.owner != system_program::id() {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                account.assign(&owner);
+                record_system_write(account);
+            }
+            _ => return Err(ProgramError::InvalidInstructionData),
+        }
+        Ok(())
+    }
+}
+
+fn transfer(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if !from.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *from.owner != system_program::id() || !from.data_is_empty() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if from.key == to.key {
+        return if from.lamports() < lamports { Err(system_error(SystemError::ResultWithNegativeLamports)) } else { Ok(()) };
+    }
+    let from_lamports =
+        from.lamports().checked_sub(lamports).ok_or(system_error(SystemError::ResultWithNegativeLamports))?;
+    let to_lamports = to.lamports().checked_add(lamports).ok_or(ProgramError::ArithmeticOverflow)?;
+    **from.lamports.borrow_mut() = from_lamports;
+    **to.lamports.borrow_mut() = to_lamports;
+    record_system_write(from);
+    Ok(())
+}
+
+// The system program's own errors, so that callers see the codes it returns
+fn system_error(error: SystemError) -> ProgramError {
+    ProgramError::Custom(error as u32)
+}
+
+fn record_system_write(account: &AccountInfo) {
+    SYSTEM_WRITES.with(|writes| writes.borrow_mut().push(*account.key));
+}
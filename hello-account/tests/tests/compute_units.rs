@@ -0,0 +1,82 @@
+//! Compute units each instruction consumes under the BPF runtime, against the
+//! budgets in `tests/snapshots/compute_units.json`.
+//!
+//! Needs the SBF build of the program; from `hello-account`:
+//!
+//! ```sh
+//! (cd program && cargo build-sbf)
+//! (cd tests && cargo test --test compute_units -- --ignored --nocapture)
+//! ```
+//!
+//! After an intentional change, re-bless with `BLESS=1` and commit the
+//! baseline.
+
+use hello_account::state::{Greeting, MAX_MESSAGE_LEN};
+use hello_account_client::instructions;
+use hello_account_tests::{budget, GreetingFixture, Outcome};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use std::{collections::BTreeMap, path::Path};
+
+const BASELINE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/compute_units.json");
+
+// Small enough to catch a new validation step, large enough to absorb the
+// noise of a toolchain bump
+const TOLERANCE_PERCENT: u64 = 5;
+
+#[derive(Clone, Copy, Debug)]
+enum Case {
+    Initialize,
+    UpdateMessage { len: usize },
+    IncrementOnly,
+    Close,
+}
+
+const CASES: [(&str, Case); 6] = [
+    ("initialize", Case::Initialize),
+    ("update_message/empty", Case::UpdateMessage { len: 0 }),
+    ("update_message/short", Case::UpdateMessage { len: 16 }),
+    ("update_message/max", Case::UpdateMessage { len: MAX_MESSAGE_LEN }),
+    ("increment_only", Case::IncrementOnly),
+    ("close", Case::Close),
+];
+
+/// The worst case to decode: a greeting whose message fills the account.
+fn full_greeting(owner: Pubkey) -> Greeting {
+    Greeting { owner, count: 7, message: "x".repeat(MAX_MESSAGE_LEN) }
+}
+
+async fn measure(case: Case) -> Outcome {
+    let mut fixture = GreetingFixture::start_sbf().await;
+    let owner = fixture.create_owner().await;
+    let greeting = Keypair::new();
+    if !matches!(case, Case::Initialize) {
+        fixture.set_greeting(greeting.pubkey(), fixture.program_id, &full_greeting(owner.pubkey())).await;
+    }
+
+    let program_id = fixture.program_id;
+    match case {
+        Case::Initialize => fixture.initialize(&greeting, owner.pubkey()).await,
+        Case::UpdateMessage { len } => {
+            let instruction = instructions::update_message(program_id, owner.pubkey(), greeting.pubkey(), &"y".repeat(len));
+            fixture.process(&[instruction], &[&owner]).await
+        }
+        Case::IncrementOnly => fixture.increment(&owner, greeting.pubkey()).await,
+        Case::Close => fixture.close(&owner, greeting.pubkey(), owner.pubkey()).await,
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF build: run `cargo build-sbf` in hello-account/program, then pass --ignored"]
+async fn compute_units_stay_within_budget() {
+    let mut measured = BTreeMap::new();
+    for (name, case) in CASES {
+        let outcome = measure(case).await;
+        outcome.assert_ok();
+        assert!(outcome.compute_units > 0, "{} consumed no compute units; is the BPF runtime metering?", name);
+        measured.insert(name.to_string(), outcome.compute_units);
+    }
+    budget::check(Path::new(BASELINE), &measured, TOLERANCE_PERCENT);
+}
@@ -0,0 +1,63 @@
+use borsh::BorshSerialize;
+use hello_account::state::{Greeting, MAX_MESSAGE_LEN};
+use hello_account_tests::{
+    decode_greeting,
+    strategies::{greeting, oversized_message, pubkey},
+};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn storable_greetings_fit_the_account(greeting in greeting()) {
+        let encoded = greeting.try_to_vec().unwrap();
+        prop_assert_eq!(encoded.len(), 32 + 8 + 4 + greeting.message.len());
+        prop_assert!(encoded.len() <= Greeting::space());
+    }
+
+    #[test]
+    fn greeting_round_trips_through_account_data(greeting in greeting()) {
+        let mut data = vec![0; Greeting::space()];
+        greeting.store(&mut data).unwrap();
+
+        let encoded_len = greeting.try_to_vec().unwrap().len();
+        prop_assert!(data[encoded_len..].iter().all(|&byte| byte == 0));
+        prop_assert_eq!(decode_greeting(&data), greeting);
+    }
+
+    #[test]
+    fn store_clears_a_longer_previous_message(previous in greeting(), greeting in greeting()) {
+        let mut data = vec![0; Greeting::space()];
+        previous.store(&mut data).unwrap();
+        greeting.store(&mut data).unwrap();
+
+        let mut expected = vec![0; Greeting::space()];
+        greeting.store(&mut expected).unwrap();
+        prop_assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn load_rejects_non_zero_padding(greeting in greeting(), byte in 1..=u8::MAX) {
+        let mut data = vec![0; Greeting::space()];
+        greeting.store(&mut data).unwrap();
+        let encoded_len = greeting.try_to_vec().unwrap().len();
+        prop_assume!(encoded_len < data.len());
+
+        *data.last_mut().unwrap() = byte;
+        prop_assert!(Greeting::load(&data).is_err());
+    }
+
+    #[test]
+    fn oversized_message_does_not_fit(owner in pubkey(), count in any::<u64>(), message in oversized_message()) {
+        prop_assert!(message.len() > MAX_MESSAGE_LEN);
+        let greeting = Greeting { owner, count, message };
+
+        let mut data = vec![0; Greeting::space()];
+        prop_assert!(greeting.store(&mut data).is_err());
+    }
+}
+
+#[test]
+fn space_matches_a_full_greeting() {
+    let greeting = Greeting { owner: Default::default(), count: u64::MAX, message: "x".repeat(MAX_MESSAGE_LEN) };
+    assert_eq!(greeting.try_to_vec().unwrap().len(), Greeting::space());
+}
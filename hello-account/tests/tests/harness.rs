@@ -0,0 +1,203 @@
+//! The program's flows and the runtime's checks, run synchronously through
+//! `Harness` instead of a bank.
+
+use hello_account::{
+    error::HelloError,
+    state::{Greeting, MAX_MESSAGE_LEN},
+};
+use hello_account_client::instructions;
+use hello_account_tests::{Harness, ProcessInstruction, INITIAL_MESSAGE, OWNER_FUNDING};
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::TransactionError,
+};
+
+#[test]
+fn initialize_creates_a_rent_exempt_greeting() {
+    let mut harness = Harness::new();
+    let owner = Keypair::new();
+    let greeting = Keypair::new();
+    let payer_lamports = harness.lamports(harness.payer.pubkey());
+
+    harness
+        .initialize(&greeting, owner.pubkey())
+        .assert_ok()
+        .assert_log("Instruction: Initialize")
+        .assert_log(&format!("Greeting account created for owner: {}", owner.pubkey()));
+
+    harness.assert_greeting(greeting.pubkey(), owner.pubkey(), 0, INITIAL_MESSAGE);
+    let rent = harness.rent().minimum_balance(Greeting::space());
+    assert_eq!(harness.lamports(greeting.pubkey()), rent);
+    assert_eq!(harness.lamports(harness.payer.pubkey()), payer_lamports - rent);
+}
+
+#[test]
+fn initialize_rejects_an_existing_account() {
+    let mut harness = Harness::new();
+    let owner = Keypair::new();
+    let greeting = Keypair::new();
+    harness.set_lamports(greeting.pubkey(), 1);
+
+    harness.initialize(&greeting, owner.pubkey()).assert_instruction_error(InstructionError::Custom(0));
+    assert_eq!(harness.lamports(greeting.pubkey()), 1);
+}
+
+#[test]
+fn update_message() {
+    let mut harness = Harness::new();
+    let owner = harness.create_owner();
+    let greeting = harness.create_greeting(owner.pubkey());
+
+    harness.update(&owner, greeting, "Updated message!").assert_ok().assert_log("Message updated. Count: 1");
+    harness.assert_greeting(greeting, owner.pubkey(), 1, "Updated message!");
+}
+
+#[test]
+fn increment_only() {
+    let mut harness = Harness::new();
+    let owner = harness.create_owner();
+    let greeting = harness.create_greeting(owner.pubkey());
+
+    harness.increment(&owner, greeting).assert_ok();
+    harness.increment(&owner, greeting).assert_ok().assert_log("Count incremented to: 2");
+    harness.assert_greeting(greeting, owner.pubkey(), 2, INITIAL_MESSAGE);
+}
+
+#[test]
+fn unauthorized_update() {
+    let mut harness = Harness::new();
+    let owner = harness.create_owner();
+    let intruder = harness.create_owner();
+    let greeting = harness.create_greeting(owner.pubkey());
+
+    harness.update(&intruder, greeting, "Hacked!").assert_hello_error(HelloError::Unauthorized);
+    harness.assert_greeting(greeting, owner.pubkey(), 0, INITIAL_MESSAGE);
+}
+
+#[test]
+fn close_refunds_the_destination() {
+    let mut harness = Harness::new();
+    let owner = harness.create_owner();
+    let greeting = harness.create_greeting(owner.pubkey());
+    let rent = harness.lamports(greeting);
+
+    harness.close(&owner, greeting, owner.pubkey()).assert_ok();
+    harness.assert_closed(greeting);
+    assert_eq!(harness.lamports(owner.pubkey()), OWNER_FUNDING + rent);
+}
+
+#[test]
+fn close_into_the_greeting_is_unbalanced() {
+    let mut harness = Harness::new();
+    let owner = harness.create_owner();
+    let greeting = Pubkey::new_unique();
+    let full = Greeting { owner: owner.pubkey(), count: 0, message: "x".repeat(MAX_MESSAGE_LEN) };
+    harness.set_greeting(greeting, harness.program_id, &full);
+
+    harness
+        .close(&owner, greeting, greeting)
+        .assert_transaction_error(TransactionError::InstructionError(0, InstructionError::UnbalancedInstruction));
+    assert_eq!(harness.greeting(greeting), full);
+}
+
+#[test]
+fn failed_transactions_keep_no_changes() {
+    let mut harness = Harness::new();
+    let owner = Keypair::new();
+    let greeting = Keypair::new();
+    let payer_lamports = harness.lamports(harness.payer.pubkey());
+
+    let initialize = instructions::initialize(harness.program_id, harness.payer.pubkey(), greeting.pubkey(), owner.pubkey());
+    let again = instructions::initialize(harness.program_id, harness.payer.pubkey(), greeting.pubkey(), owner.pubkey());
+    harness
+        .process(&[initialize, again])
+        .assert_transaction_error(TransactionError::InstructionError(1, InstructionError::Custom(0)));
+
+    harness.assert_closed(greeting.pubkey());
+    assert_eq!(harness.lamports(harness.payer.pubkey()), payer_lamports);
+}
+
+#[test]
+fn system_transfers_run_through_the_stubs() {
+    let mut harness = Harness::new();
+    let owner = harness.create_owner();
+    let recipient = Pubkey::new_unique();
+
+    harness.process(&[system_instruction::transfer(&owner.pubkey(), &recipient, OWNER_FUNDING / 2)]).assert_ok();
+    assert_eq!(harness.lamports(owner.pubkey()), OWNER_FUNDING / 2);
+    assert_eq!(harness.lamports(recipient), OWNER_FUNDING / 2);
+}
+
+#[test]
+fn transactions_may_not_leave_accounts_rent_paying() {
+    let mut harness = Harness::new();
+    let owner = harness.create_owner();
+    let recipient = Pubkey::new_unique();
+
+    harness
+        .process(&[system_instruction::transfer(&owner.pubkey(), &recipient, 1)])
+        .assert_transaction_error(TransactionError::InsufficientFundsForRent { account_index: 1 });
+    assert_eq!(harness.lamports(recipient), 0);
+}
+
+// Processors that break one of the runtime's rules with their first two accounts
+
+fn debit_first_account(_: &Pubkey, accounts: &[AccountInfo], _: &[u8]) -> ProgramResult {
+    **accounts[0].lamports.borrow_mut() -= 1;
+    **accounts[1].lamports.borrow_mut() += 1;
+    Ok(())
+}
+
+fn write_first_account(_: &Pubkey, accounts: &[AccountInfo], _: &[u8]) -> ProgramResult {
+    accounts[0].data.borrow_mut()[0] = 1;
+    Ok(())
+}
+
+fn mint_lamports(_: &Pubkey, accounts: &[AccountInfo], _: &[u8]) -> ProgramResult {
+    **accounts[0].lamports.borrow_mut() += 1;
+    Ok(())
+}
+
+/// Runs `process_instruction` with an account holding data, owned by the
+/// program or not and writable or not, followed by a system account.
+fn run_rogue(process_instruction: ProcessInstruction, owned: bool, writable: bool) -> TransactionError {
+    let mut harness = Harness::with_processor(process_instruction);
+    let first = Pubkey::new_unique();
+    let second = harness.create_owner().pubkey();
+    let owner = if owned { harness.program_id } else { Pubkey::new_unique() };
+    harness.set_account(first, Account { lamports: OWNER_FUNDING, data: vec![0; 8], owner, executable: false, rent_epoch: 0 });
+
+    let accounts = vec![
+        if writable { AccountMeta::new(first, false) } else { AccountMeta::new_readonly(first, false) },
+        AccountMeta::new(second, false),
+    ];
+    let outcome = harness.process(&[Instruction::new_with_bytes(harness.program_id, &[], accounts)]);
+    assert_eq!(harness.lamports(first), OWNER_FUNDING, "a failed instruction keeps no changes");
+    outcome.result.expect_err("the rogue processor breaks a rule")
+}
+
+#[test]
+fn runtime_rules_are_enforced() {
+    let cases = [
+        (debit_first_account as ProcessInstruction, false, true, InstructionError::ExternalAccountLamportSpend),
+        (debit_first_account, true, false, InstructionError::ReadonlyLamportChange),
+        (write_first_account, false, true, InstructionError::ExternalAccountDataModified),
+        (write_first_account, true, false, InstructionError::ReadonlyDataModified),
+        (mint_lamports, true, true, InstructionError::UnbalancedInstruction),
+    ];
+    for (process_instruction, owned, writable, expected) in cases {
+        assert_eq!(
+            run_rogue(process_instruction, owned, writable),
+            TransactionError::InstructionError(0, expected.clone()),
+            "owned: {}, writable: {}",
+            owned,
+            writable
+        );
+    }
+}
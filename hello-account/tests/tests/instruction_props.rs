@@ -0,0 +1,71 @@
+use hello_account::instruction::HelloInstruction;
+use hello_account_client::instructions;
+use hello_account_tests::strategies::{instruction, message, pubkey};
+use proptest::{collection::vec, prelude::*};
+use solana_sdk::program_error::ProgramError;
+
+proptest! {
+    #[test]
+    fn pack_unpack_round_trips(instruction in instruction()) {
+        prop_assert_eq!(HelloInstruction::unpack(&instruction.pack()), Ok(instruction));
+    }
+
+    #[test]
+    fn client_builders_match_program_decoding(
+        program_id in pubkey(),
+        owner in pubkey(),
+        greeting in pubkey(),
+        message in message(400),
+    ) {
+        let decode = |data: &[u8]| HelloInstruction::unpack(data);
+
+        let built = instructions::initialize(program_id, owner, greeting, owner);
+        prop_assert_eq!(decode(&built.data), Ok(HelloInstruction::Initialize { owner }));
+
+        let built = instructions::update_message(program_id, owner, greeting, &message);
+        prop_assert_eq!(decode(&built.data), Ok(HelloInstruction::UpdateMessage { message }));
+
+        let built = instructions::increment_only(program_id, owner, greeting);
+        prop_assert_eq!(decode(&built.data), Ok(HelloInstruction::IncrementOnly));
+
+        let built = instructions::close(program_id, owner, greeting, owner);
+        prop_assert_eq!(decode(&built.data), Ok(HelloInstruction::Close));
+    }
+
+    #[test]
+    fn arbitrary_bytes_decode_or_reject_cleanly(data in vec(any::<u8>(), 0..300)) {
+        match HelloInstruction::unpack(&data) {
+            // Fixed-size variants ignore trailing bytes
+            Ok(instruction) => prop_assert!(data.starts_with(&instruction.pack())),
+            Err(error) => prop_assert_eq!(error, ProgramError::InvalidInstructionData),
+        }
+    }
+
+    #[test]
+    fn truncated_owner_is_rejected(owner in vec(any::<u8>(), 0..32)) {
+        let mut data = vec![0];
+        data.extend_from_slice(&owner);
+        prop_assert_eq!(HelloInstruction::unpack(&data), Err(ProgramError::InvalidInstructionData));
+    }
+
+    #[test]
+    fn invalid_utf8_message_is_rejected(
+        bytes in vec(any::<u8>(), 1..300).prop_filter("valid UTF-8", |bytes| std::str::from_utf8(bytes).is_err()),
+    ) {
+        let mut data = vec![1];
+        data.extend_from_slice(&bytes);
+        prop_assert_eq!(HelloInstruction::unpack(&data), Err(ProgramError::InvalidInstructionData));
+    }
+
+    #[test]
+    fn unknown_variant_is_rejected(variant in 4u8.., rest in vec(any::<u8>(), 0..64)) {
+        let mut data = vec![variant];
+        data.extend_from_slice(&rest);
+        prop_assert_eq!(HelloInstruction::unpack(&data), Err(ProgramError::InvalidInstructionData));
+    }
+}
+
+#[test]
+fn empty_input_is_rejected() {
+    assert_eq!(HelloInstruction::unpack(&[]), Err(ProgramError::InvalidInstructionData));
+}
@@ -0,0 +1,84 @@
+//! Pins the byte layout of greeting accounts and the wire format of every
+//! instruction, which deployed accounts and existing clients depend on.
+//!
+//! After an intentional change, re-bless from `hello-account/tests` with
+//! `BLESS=1 cargo test --test layout` and commit `tests/snapshots/layout.json`.
+
+use hello_account::state::{Greeting, MAX_MESSAGE_LEN};
+use hello_account_client::{
+    inspect::{COUNT_OFFSET, MESSAGE_LEN_OFFSET, MESSAGE_OFFSET, OWNER_OFFSET},
+    instructions,
+};
+use hello_account_tests::{golden, INITIAL_MESSAGE};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use std::{collections::BTreeMap, path::Path};
+
+const SNAPSHOTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/layout.json");
+
+// Distinct byte patterns, so that a moved field shows up in the diff
+fn key(seed: u8) -> Pubkey {
+    Pubkey::new_from_array(std::array::from_fn(|i| seed.wrapping_add(i as u8)))
+}
+
+/// The account data the program would store for `greeting`.
+fn account_data(greeting: &Greeting) -> Vec<u8> {
+    let mut data = vec![0; Greeting::space()];
+    greeting.store(&mut data).unwrap();
+    data
+}
+
+/// Each account as its signer and writable flags followed by its key.
+fn account_metas(instruction: &Instruction) -> Vec<u8> {
+    instruction
+        .accounts
+        .iter()
+        .flat_map(|meta| [&[meta.is_signer as u8, meta.is_writable as u8][..], meta.pubkey.as_ref()].concat())
+        .collect()
+}
+
+#[test]
+fn layout_matches_snapshots() {
+    let (program_id, payer, owner, greeting) = (key(0x10), key(0x20), key(0x30), key(0x40));
+
+    let greetings = [
+        ("greeting/initial", Greeting { owner, count: 0, message: INITIAL_MESSAGE.to_string() }),
+        ("greeting/unicode", Greeting { owner, count: 0x0102_0304_0506_0708, message: "héllo 👋".to_string() }),
+        ("greeting/full", Greeting { owner, count: u64::MAX, message: "x".repeat(MAX_MESSAGE_LEN) }),
+    ];
+    let instructions = [
+        ("instruction/initialize", instructions::initialize(program_id, payer, greeting, owner)),
+        ("instruction/update_message", instructions::update_message(program_id, owner, greeting, "Hello, integrators!")),
+        ("instruction/update_message_empty", instructions::update_message(program_id, owner, greeting, "")),
+        ("instruction/increment_only", instructions::increment_only(program_id, owner, greeting)),
+        ("instruction/close", instructions::close(program_id, owner, greeting, payer)),
+    ];
+
+    let mut snapshots = BTreeMap::new();
+    for (name, greeting) in &greetings {
+        snapshots.insert(name.to_string(), account_data(greeting));
+    }
+    for (name, instruction) in &instructions {
+        snapshots.insert(format!("{}/data", name), instruction.data.clone());
+        snapshots.insert(format!("{}/accounts", name), account_metas(instruction));
+    }
+
+    golden::check(Path::new(SNAPSHOTS), &snapshots);
+}
+
+#[test]
+fn greeting_space_is_stable() {
+    // `get_program_greetings` filters on this size
+    assert_eq!(Greeting::space(), 244);
+}
+
+#[test]
+fn field_offsets_match_encoding() {
+    assert_eq!((OWNER_OFFSET, COUNT_OFFSET, MESSAGE_LEN_OFFSET, MESSAGE_OFFSET), (0, 32, 40, 44));
+
+    let greeting = Greeting { owner: key(0x30), count: 0x0102_0304_0506_0708, message: "offsets".to_string() };
+    let data = account_data(&greeting);
+    assert_eq!(&data[OWNER_OFFSET..COUNT_OFFSET], greeting.owner.as_ref());
+    assert_eq!(data[COUNT_OFFSET..MESSAGE_LEN_OFFSET], greeting.count.to_le_bytes());
+    assert_eq!(data[MESSAGE_LEN_OFFSET..MESSAGE_OFFSET], 7u32.to_le_bytes());
+    assert_eq!(&data[MESSAGE_OFFSET..MESSAGE_OFFSET + 7], b"offsets");
+}
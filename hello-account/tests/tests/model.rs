@@ -0,0 +1,169 @@
+//! Random sequences of operations from several actors, run against both the
+//! reference model and the program under `ProgramTest`. After every step the
+//! result, every greeting account and every actor's balance must match the
+//! model; a divergence shrinks to the shortest sequence that still shows it.
+//!
+//! Cases share one bank, each with its own actors and greeting accounts:
+//! starting a bank per case makes shrinking take minutes.
+
+use hello_account::state::{Greeting, MAX_MESSAGE_LEN};
+use hello_account_tests::{
+    decode_greeting,
+    model::{Model, Operation, ACTORS, SLOTS},
+    strategies::operation,
+    GreetingFixture, Outcome, OWNER_FUNDING,
+};
+use proptest::{
+    collection::vec,
+    prelude::*,
+    test_runner::{TestCaseError, TestError, TestRunner},
+};
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+use std::cell::RefCell;
+use tokio::runtime::Runtime;
+
+const MAX_OPERATIONS: usize = 24;
+
+/// Actors and greeting accounts new to the bank, and the model they are
+/// checked against.
+struct World<'a> {
+    fixture: &'a mut GreetingFixture,
+    actors: Vec<Keypair>,
+    slots: Vec<Keypair>,
+    model: Model,
+}
+
+impl<'a> World<'a> {
+    async fn start(fixture: &'a mut GreetingFixture) -> Self {
+        let mut actors = Vec::with_capacity(ACTORS);
+        for _ in 0..ACTORS {
+            actors.push(fixture.create_owner().await);
+        }
+        let rent = fixture.context.banks_client.get_rent().await.expect("banks server reachable");
+        let model = Model::new(rent.minimum_balance(Greeting::space()));
+        Self { fixture, actors, slots: (0..SLOTS).map(|_| Keypair::new()).collect(), model }
+    }
+
+    async fn run(&mut self, operation: &Operation) -> Outcome {
+        let fixture = &mut *self.fixture;
+        match operation {
+            Operation::Initialize { slot, owner } => fixture.initialize(&self.slots[*slot], self.actors[*owner].pubkey()).await,
+            Operation::UpdateMessage { actor, slot, message } => {
+                fixture.update(&self.actors[*actor], self.slots[*slot].pubkey(), message).await
+            }
+            Operation::IncrementOnly { actor, slot } => fixture.increment(&self.actors[*actor], self.slots[*slot].pubkey()).await,
+            Operation::Close { actor, slot, destination } => {
+                let destination = self.actors[*destination].pubkey();
+                fixture.close(&self.actors[*actor], self.slots[*slot].pubkey(), destination).await
+            }
+            Operation::Preset { slot, owner, count, message } => {
+                let greeting = Greeting { owner: self.actors[*owner].pubkey(), count: *count, message: message.clone() };
+                let program_id = fixture.program_id;
+                fixture.set_greeting(self.slots[*slot].pubkey(), program_id, &greeting).await;
+                Outcome { result: Ok(()), logs: Vec::new(), compute_units: 0 }
+            }
+        }
+    }
+
+    /// Runs `operation` against both and compares the results, then the
+    /// state of every account.
+    async fn step(&mut self, operation: &Operation) -> Result<(), TestCaseError> {
+        let expected = self.model.apply(operation).map_err(|error| TransactionError::InstructionError(0, error));
+        let outcome = self.run(operation).await;
+        prop_assert_eq!(&outcome.result, &expected, "result of {:?}; logs:\n{}", operation, outcome.logs.join("\n"));
+
+        for (slot, keypair) in self.slots.iter().enumerate() {
+            let address = keypair.pubkey();
+            let account = self.fixture.account(address).await;
+            match (&self.model.greetings[slot], account) {
+                (None, None) => {}
+                (None, Some(account)) => prop_assert!(false, "slot {} should not exist, has {:?}", slot, account),
+                (Some(expected), None) => prop_assert!(false, "slot {} should hold {:?}, does not exist", slot, expected),
+                (Some(expected), Some(account)) => {
+                    prop_assert_eq!(account.owner, self.fixture.program_id, "program owning slot {}", slot);
+                    prop_assert_eq!(account.lamports, expected.lamports, "lamports of slot {}", slot);
+                    let greeting = decode_greeting(&account.data);
+                    let expected =
+                        Greeting { owner: self.actors[expected.owner].pubkey(), count: expected.count, message: expected.message.clone() };
+                    prop_assert_eq!(greeting, expected, "greeting in slot {}", slot);
+                }
+            }
+        }
+        for (actor, keypair) in self.actors.iter().enumerate() {
+            let lamports = self.fixture.lamports(keypair.pubkey()).await;
+            prop_assert_eq!(lamports, OWNER_FUNDING + self.model.refunds[actor], "balance of actor {}", actor);
+        }
+        Ok(())
+    }
+}
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_current_thread().enable_all().build().expect("tokio runtime")
+}
+
+async fn run_sequence(fixture: &mut GreetingFixture, operations: &[Operation]) -> Result<(), TestCaseError> {
+    let mut world = World::start(fixture).await;
+    for (index, operation) in operations.iter().enumerate() {
+        world
+            .step(operation)
+            .await
+            .map_err(|error| TestCaseError::fail(format!("step {} of {}: {}", index + 1, operations.len(), error)))?;
+    }
+    Ok(())
+}
+
+/// Runs `operations` on a bank of their own.
+fn run_alone(operations: &[Operation]) {
+    let runtime = runtime();
+    runtime.block_on(async {
+        let mut fixture = GreetingFixture::start().await;
+        if let Err(error) = run_sequence(&mut fixture, operations).await {
+            panic!("{}", error);
+        }
+    });
+}
+
+#[test]
+fn program_matches_the_model() {
+    let runtime = runtime();
+    let fixture = RefCell::new(runtime.block_on(GreetingFixture::start()));
+    let mut runner = TestRunner::new(ProptestConfig { cases: 64, ..ProptestConfig::default() });
+
+    let result = runner.run(&vec(operation(), 1..=MAX_OPERATIONS), |operations| {
+        runtime.block_on(run_sequence(&mut fixture.borrow_mut(), &operations))
+    });
+    match result {
+        Ok(()) => {}
+        Err(TestError::Fail(reason, operations)) => {
+            panic!("{}\nminimal failing sequence: {:#?}", reason, operations)
+        }
+        Err(TestError::Abort(reason)) => panic!("aborted: {}", reason),
+    }
+}
+
+#[test]
+fn closed_greetings_reject_every_operation() {
+    run_alone(&[
+        Operation::Preset { slot: 0, owner: 0, count: 1, message: "x".repeat(MAX_MESSAGE_LEN) },
+        Operation::Close { actor: 0, slot: 0, destination: 1 },
+        Operation::UpdateMessage { actor: 0, slot: 0, message: "again".to_string() },
+        Operation::IncrementOnly { actor: 0, slot: 0 },
+        Operation::Close { actor: 0, slot: 0, destination: 0 },
+        Operation::Initialize { slot: 0, owner: 2 },
+    ]);
+}
+
+#[test]
+fn counts_stop_at_u64_max() {
+    let full = "x".repeat(MAX_MESSAGE_LEN);
+    run_alone(&[
+        Operation::Preset { slot: 1, owner: 2, count: u64::MAX - 1, message: full.clone() },
+        Operation::IncrementOnly { actor: 2, slot: 1 },
+        Operation::IncrementOnly { actor: 2, slot: 1 },
+        Operation::UpdateMessage { actor: 2, slot: 1, message: full },
+        Operation::IncrementOnly { actor: 0, slot: 1 },
+    ]);
+}
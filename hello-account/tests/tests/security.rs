@@ -0,0 +1,253 @@
+//! Every instruction against every attack, each pinned to the exact error it
+//! fails with. Errors raised by the system program or the runtime rather than
+//! by this program (e.g. `UnbalancedInstruction`) are pinned too: if the
+//! program starts rejecting such a case itself, move the expectation to the
+//! program's error.
+
+use borsh::BorshSerialize;
+use hello_account::{
+    error::HelloError,
+    state::{Greeting, MAX_MESSAGE_LEN},
+};
+use hello_account_client::instructions;
+use hello_account_tests::{GreetingFixture, Outcome};
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::TransactionError,
+};
+
+// `SystemError::AccountAlreadyInUse`, from the `create_account` CPI
+const ACCOUNT_ALREADY_IN_USE: u32 = 0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Variant {
+    Initialize,
+    UpdateMessage,
+    IncrementOnly,
+    Close,
+}
+
+const VARIANTS: [Variant; 4] = [Variant::Initialize, Variant::UpdateMessage, Variant::IncrementOnly, Variant::Close];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Attack {
+    /// The owner (or payer) account is passed without signing
+    NonSignerOwner,
+    /// Someone other than the greeting's owner signs
+    WrongOwner,
+    /// The greeting account belongs to another program
+    ForeignGreeting,
+    /// Another account is passed in place of the system program
+    SpoofedSystemProgram,
+    /// The signer is passed in every account slot
+    DuplicateAccounts,
+    /// The greeting is closed, refunded and used again in one transaction
+    ClosedThenRevived,
+    /// Close pays the greeting's lamports back into the greeting
+    DestinationIsGreeting,
+    /// The greeting account is too small to hold a greeting
+    UndersizedData,
+}
+
+const ATTACKS: [Attack; 8] = [
+    Attack::NonSignerOwner,
+    Attack::WrongOwner,
+    Attack::ForeignGreeting,
+    Attack::SpoofedSystemProgram,
+    Attack::DuplicateAccounts,
+    Attack::ClosedThenRevived,
+    Attack::DestinationIsGreeting,
+    Attack::UndersizedData,
+];
+
+#[derive(Debug)]
+enum Expected {
+    /// The instruction at `index` fails with `error`
+    Error { index: u8, error: InstructionError },
+    /// The attack has no meaning for this instruction, for the reason given
+    NotApplicable(&'static str),
+}
+
+fn fails(error: InstructionError) -> Expected {
+    Expected::Error { index: 0, error }
+}
+
+fn fails_with(error: HelloError) -> Expected {
+    fails(hello_error(error))
+}
+
+fn hello_error(error: HelloError) -> InstructionError {
+    InstructionError::Custom(error as u32)
+}
+
+fn borsh_error() -> InstructionError {
+    InstructionError::BorshIoError("Unknown".to_string())
+}
+
+fn cases() -> Vec<(Attack, Variant, Expected)> {
+    use Attack::*;
+    use Variant::*;
+
+    vec![
+        (NonSignerOwner, Initialize, fails(InstructionError::MissingRequiredSignature)),
+        (NonSignerOwner, UpdateMessage, fails(InstructionError::MissingRequiredSignature)),
+        (NonSignerOwner, IncrementOnly, fails(InstructionError::MissingRequiredSignature)),
+        (NonSignerOwner, Close, fails(InstructionError::MissingRequiredSignature)),
+        (WrongOwner, Initialize, Expected::NotApplicable("anyone may create a greeting for any owner")),
+        (WrongOwner, UpdateMessage, fails_with(HelloError::Unauthorized)),
+        (WrongOwner, IncrementOnly, fails_with(HelloError::Unauthorized)),
+        (WrongOwner, Close, fails_with(HelloError::Unauthorized)),
+        (ForeignGreeting, Initialize, fails(InstructionError::Custom(ACCOUNT_ALREADY_IN_USE))),
+        (ForeignGreeting, UpdateMessage, fails(InstructionError::IncorrectProgramId)),
+        (ForeignGreeting, IncrementOnly, fails(InstructionError::IncorrectProgramId)),
+        (ForeignGreeting, Close, fails(InstructionError::IncorrectProgramId)),
+        (SpoofedSystemProgram, Initialize, fails(InstructionError::IncorrectProgramId)),
+        (SpoofedSystemProgram, UpdateMessage, Expected::NotApplicable("takes no system program")),
+        (SpoofedSystemProgram, IncrementOnly, Expected::NotApplicable("takes no system program")),
+        (SpoofedSystemProgram, Close, Expected::NotApplicable("takes no system program")),
+        (DuplicateAccounts, Initialize, fails(InstructionError::Custom(ACCOUNT_ALREADY_IN_USE))),
+        (DuplicateAccounts, UpdateMessage, fails(InstructionError::IncorrectProgramId)),
+        (DuplicateAccounts, IncrementOnly, fails(InstructionError::IncorrectProgramId)),
+        (DuplicateAccounts, Close, fails(InstructionError::IncorrectProgramId)),
+        (ClosedThenRevived, Initialize, Expected::Error { index: 2, error: InstructionError::Custom(ACCOUNT_ALREADY_IN_USE) }),
+        (ClosedThenRevived, UpdateMessage, Expected::Error { index: 2, error: hello_error(HelloError::Unauthorized) }),
+        (ClosedThenRevived, IncrementOnly, Expected::Error { index: 2, error: hello_error(HelloError::Unauthorized) }),
+        (ClosedThenRevived, Close, Expected::Error { index: 2, error: hello_error(HelloError::Unauthorized) }),
+        (DestinationIsGreeting, Initialize, Expected::NotApplicable("takes no destination")),
+        (DestinationIsGreeting, UpdateMessage, Expected::NotApplicable("takes no destination")),
+        (DestinationIsGreeting, IncrementOnly, Expected::NotApplicable("takes no destination")),
+        (DestinationIsGreeting, Close, fails(InstructionError::UnbalancedInstruction)),
+        (UndersizedData, Initialize, fails(InstructionError::Custom(ACCOUNT_ALREADY_IN_USE))),
+        (UndersizedData, UpdateMessage, fails(borsh_error())),
+        (UndersizedData, IncrementOnly, fails(borsh_error())),
+        (UndersizedData, Close, fails(borsh_error())),
+    ]
+}
+
+/// A greeting whose message fills the account exactly, so that the attacks
+/// do not depend on how the unused tail of the account is decoded.
+fn full_greeting(owner: Pubkey) -> Greeting {
+    Greeting { owner, count: 7, message: "x".repeat(MAX_MESSAGE_LEN) }
+}
+
+/// The honest version of `variant`, signed by `owner`.
+fn honest(fixture: &GreetingFixture, variant: Variant, owner: Pubkey, greeting: Pubkey) -> Instruction {
+    let program_id = fixture.program_id;
+    match variant {
+        Variant::Initialize => instructions::initialize(program_id, owner, greeting, owner),
+        Variant::UpdateMessage => instructions::update_message(program_id, owner, greeting, "pwned"),
+        Variant::IncrementOnly => instructions::increment_only(program_id, owner, greeting),
+        Variant::Close => instructions::close(program_id, owner, greeting, owner),
+    }
+}
+
+async fn run(attack: Attack, variant: Variant) -> Outcome {
+    let mut fixture = GreetingFixture::start().await;
+    let owner = fixture.create_owner().await;
+    let greeting = Keypair::new();
+
+    // Every variant but Initialize needs an existing greeting to attack
+    if variant != Variant::Initialize {
+        fixture.set_greeting(greeting.pubkey(), fixture.program_id, &full_greeting(owner.pubkey())).await;
+    }
+    let mut instruction = honest(&fixture, variant, owner.pubkey(), greeting.pubkey());
+    let signers = [&owner, &greeting];
+
+    match attack {
+        Attack::NonSignerOwner => {
+            instruction.accounts[0].is_signer = false;
+            fixture.process(&[instruction], &[&greeting]).await
+        }
+        Attack::WrongOwner => {
+            let attacker = fixture.create_owner().await;
+            let instruction = honest(&fixture, variant, attacker.pubkey(), greeting.pubkey());
+            fixture.process(&[instruction], &[&attacker, &greeting]).await
+        }
+        Attack::ForeignGreeting => {
+            let other_program = Pubkey::new_unique();
+            fixture.set_greeting(greeting.pubkey(), other_program, &full_greeting(owner.pubkey())).await;
+            fixture.process(&[instruction], &signers).await
+        }
+        Attack::SpoofedSystemProgram => {
+            instruction.accounts[2].pubkey = Pubkey::new_unique();
+            fixture.process(&[instruction], &signers).await
+        }
+        Attack::DuplicateAccounts => {
+            // Initialize keeps its system program so that only the greeting is duplicated
+            for meta in instruction.accounts.iter_mut().take(if variant == Variant::Initialize { 2 } else { 3 }) {
+                meta.pubkey = owner.pubkey();
+            }
+            fixture.process(&[instruction], &[&owner]).await
+        }
+        Attack::ClosedThenRevived => {
+            if variant == Variant::Initialize {
+                fixture.set_greeting(greeting.pubkey(), fixture.program_id, &full_greeting(owner.pubkey())).await;
+            }
+            let rent = fixture.lamports(greeting.pubkey()).await;
+            let close = instructions::close(fixture.program_id, owner.pubkey(), greeting.pubkey(), owner.pubkey());
+            let refund = system_instruction::transfer(&fixture.payer(), &greeting.pubkey(), rent);
+            fixture.process(&[close, refund, instruction], &signers).await
+        }
+        Attack::DestinationIsGreeting => {
+            instruction.accounts[2].pubkey = greeting.pubkey();
+            fixture.process(&[instruction], &signers).await
+        }
+        Attack::UndersizedData => {
+            // Owner and count, but no message
+            let data = full_greeting(owner.pubkey()).try_to_vec().unwrap()[..40].to_vec();
+            let rent = fixture.context.banks_client.get_rent().await.unwrap();
+            fixture.set_account(
+                greeting.pubkey(),
+                Account {
+                    lamports: rent.minimum_balance(data.len()),
+                    data,
+                    owner: fixture.program_id,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            );
+            fixture.process(&[instruction], &signers).await
+        }
+    }
+}
+
+#[test]
+fn every_attack_covers_every_instruction() {
+    let cases = cases();
+    for attack in ATTACKS {
+        for variant in VARIANTS {
+            let rows: Vec<_> = cases.iter().filter(|(a, v, _)| *a == attack && *v == variant).collect();
+            assert_eq!(rows.len(), 1, "{:?} against {:?} must be listed exactly once", attack, variant);
+            if let (_, _, Expected::NotApplicable(reason)) = rows[0] {
+                assert!(!reason.is_empty(), "{:?} against {:?} must say why it does not apply", attack, variant);
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn attacks_fail_with_exact_errors() {
+    let mut mismatches = Vec::new();
+    for (attack, variant, expected) in cases() {
+        let Expected::Error { index, error } = expected else {
+            continue;
+        };
+        let outcome = run(attack, variant).await;
+        let expected = TransactionError::InstructionError(index, error);
+        if outcome.result.as_ref().err() != Some(&expected) {
+            mismatches.push(format!(
+                "{:?} against {:?}: expected {:?}, got {:?}\n  {}",
+                attack,
+                variant,
+                expected,
+                outcome.result,
+                outcome.logs.join("\n  ")
+            ));
+        }
+    }
+    assert!(mismatches.is_empty(), "{} case(s) failed:\n{}", mismatches.len(), mismatches.join("\n"));
+}